@@ -1,8 +1,10 @@
-use crate::render::{Interpolator, Render, RenderState};
+use crate::render::{Interpolator, Leaderboard, Render, RenderState, Results};
 use either::Either;
 use futures::{SinkExt, Stream, StreamExt};
 use futures_signals::signal::{Mutable, SignalExt};
-use game_shared::{Operation, PlayerState, ViewSnapshot};
+use game_shared::replay::Replay;
+use game_shared::shield::ShieldType;
+use game_shared::{JoinInfo, Operation, PlayerState, Position, ServerMessage};
 use gloo::events::EventListener;
 use piet_web::WebRenderContext;
 use std::cell::RefCell;
@@ -17,6 +19,7 @@ use web_sys::{KeyboardEvent, MouseEvent};
 use ws_stream_wasm::{WsMessage, WsMeta};
 
 mod render;
+mod replay;
 mod util;
 
 #[derive(Copy, Clone, Debug)]
@@ -27,6 +30,8 @@ struct ControlState {
 	right: bool,
 	cursor: (i32, i32),
 	mouse_down: bool,
+	boost: bool,
+	bash: bool,
 }
 
 impl ControlState {
@@ -38,6 +43,8 @@ impl ControlState {
 			right: false,
 			cursor: (0, 0),
 			mouse_down: false,
+			boost: false,
+			bash: false,
 		}
 	}
 
@@ -53,12 +60,16 @@ impl ControlState {
 				dir: None,
 				ori: (self.cursor.1 as f32).atan2(self.cursor.0 as f32),
 				push_shield: self.mouse_down,
+				boost: self.boost,
+				bash: self.bash,
 			}
 		} else {
 			PlayerState {
 				dir: Some((dy as f32).atan2(dx as f32)),
 				ori: (self.cursor.1 as f32).atan2(self.cursor.0 as f32),
 				push_shield: self.mouse_down,
+				boost: self.boost,
+				bash: self.bash,
 			}
 		}
 	}
@@ -102,6 +113,29 @@ impl ControlState {
 	pub fn mouse_up(&mut self) {
 		self.mouse_down = false;
 	}
+
+	pub fn press_boost(&mut self) {
+		self.boost = true;
+	}
+
+	pub fn release_boost(&mut self) {
+		self.boost = false;
+	}
+
+	pub fn press_bash(&mut self) {
+		self.bash = true;
+	}
+
+	pub fn release_bash(&mut self) {
+		self.bash = false;
+	}
+}
+
+/// What to do after the start screen.
+enum Start {
+	Play,
+	/// Watch the replay in the file.
+	Watch(Vec<u8>),
 }
 
 #[wasm_bindgen(start)]
@@ -123,15 +157,38 @@ pub async fn start() {
 	let name_input = document.get_element_by_id("nameInput").unwrap();
 	let name_input: web_sys::HtmlInputElement =
 		name_input.dyn_into::<web_sys::HtmlInputElement>().map_err(|_| ()).unwrap();
-	let name_state = Mutable::new(false);
-	let name_state1 = name_state.clone();
+	let (start_sender, mut starts) = futures::channel::mpsc::unbounded();
+	let start_sender1 = start_sender.clone();
 	let _enter_listener = EventListener::new(&name_input, "keydown", move |event| {
 		let event: &KeyboardEvent = event.dyn_ref().unwrap_throw();
-		// let mut state = name_state1.lock_mut();
 		if let "Enter" = event.code().as_ref() {
-			name_state1.set(true);
+			start_sender1.unbounded_send(Start::Play).unwrap();
+		}
+	});
+
+	// Watch a replay picked from a file or recorded by the server instead of playing.
+	let replay_input = document.get_element_by_id("replayInput").unwrap();
+	let replay_input: web_sys::HtmlInputElement =
+		replay_input.dyn_into::<web_sys::HtmlInputElement>().map_err(|_| ()).unwrap();
+	let replay_input1 = replay_input.clone();
+	let start_sender2 = start_sender.clone();
+	let _file_listener = EventListener::new(&replay_input, "change", move |_event| {
+		if let Some(file) = replay_input1.files().and_then(|files| files.get(0)) {
+			let start_sender = start_sender2.clone();
+			wasm_bindgen_futures::spawn_local(async move {
+				let data = replay::read_blob(&file).await;
+				start_sender.unbounded_send(Start::Watch(data)).unwrap();
+			});
 		}
 	});
+	let replay_button = document.get_element_by_id("replayButton").unwrap();
+	let _button_listener = EventListener::new(&replay_button, "click", move |_event| {
+		let start_sender = start_sender.clone();
+		wasm_bindgen_futures::spawn_local(async move {
+			let data = replay::fetch("/replay").await;
+			start_sender.unbounded_send(Start::Watch(data)).unwrap();
+		});
+	});
 
 	// Add an event listener for `keydown` event.
 	let control_state = Mutable::new(ControlState::new());
@@ -144,6 +201,8 @@ pub async fn start() {
 			"KeyA" => state.press_left(),
 			"KeyS" => state.press_down(),
 			"KeyD" => state.press_right(),
+			"Space" | "ShiftLeft" => state.press_boost(),
+			"KeyE" => state.press_bash(),
 			_ => (),
 		}
 	})
@@ -159,6 +218,8 @@ pub async fn start() {
 			"KeyA" => state.release_left(),
 			"KeyS" => state.release_down(),
 			"KeyD" => state.release_right(),
+			"Space" | "ShiftLeft" => state.release_boost(),
+			"KeyE" => state.release_bash(),
 			_ => (),
 		}
 	})
@@ -192,36 +253,92 @@ pub async fn start() {
 	})
 	.forget();
 
-	// Wait for username input.
-	let mut name_stream = name_state.signal().to_stream();
-	name_stream.next().await;
-	name_stream.next().await;
-	name_input.style().set_property("display", "none").unwrap();
+	// Wait for username input or a replay to watch.
+	let start = starts.next().await.unwrap();
+	let input_container = document.get_element_by_id("inputContainer").unwrap();
+	let input_container: web_sys::HtmlElement =
+		input_container.dyn_into::<web_sys::HtmlElement>().map_err(|_| ()).unwrap();
+	input_container.style().set_property("display", "none").unwrap();
 
 	let window = web_sys::window().expect("Window doesn't exist.");
 	let perf = window.performance().expect("No Performance found.");
 	let mut piet_ctx = WebRenderContext::new(context, window);
 
+	if let Start::Watch(data) = start {
+		replay::watch(Replay::deserialize(&data), &canvas, &mut piet_ctx).await;
+		return;
+	}
+
+	let team_input = document.get_element_by_id("teamInput").unwrap();
+	let team_input: web_sys::HtmlSelectElement =
+		team_input.dyn_into::<web_sys::HtmlSelectElement>().map_err(|_| ()).unwrap();
+	let shield_input = document.get_element_by_id("shieldInput").unwrap();
+	let shield_input: web_sys::HtmlSelectElement =
+		shield_input.dyn_into::<web_sys::HtmlSelectElement>().map_err(|_| ()).unwrap();
+	let shield = ShieldType::ALL.get(shield_input.selected_index() as usize);
+	let join_info = JoinInfo {
+		name: name_input.value(),
+		team: team_input.value().parse().ok(),
+		shield: shield.copied().unwrap_or_default(),
+	};
 	let (ws_meta, mut ws_stream) = WsMeta::connect("ws://127.0.0.1:8080/ws", None)
 		.await
 		.expect("Websocket connection failed.");
 	ws_stream
-		.send(WsMessage::Binary(Operation::Join(name_input.value()).serialize()))
+		.send(WsMessage::Binary(Operation::Join(join_info).serialize()))
 		.await
 		.expect("Failed to send join info.");
 	let (mut ws_sender, ws_receiver) = ws_stream.split();
 	let control_state_signal = control_state.signal();
 
-	let mut key_frames = ws_receiver.filter_map(|message| match message {
-		WsMessage::Binary(data) => futures::future::ready(Some(RenderState::from(
-			ViewSnapshot::deserialize(data.as_slice()),
-		))),
+	// Snapshots are passed on for rendering while other messages are kept aside.
+	let leaderboard = Rc::new(RefCell::new(Leaderboard::default()));
+	let leaderboard1 = leaderboard.clone();
+	let results = Rc::new(RefCell::new(None));
+	let results1 = results.clone();
+	let arena = Rc::new(RefCell::new(None));
+	let arena1 = arena.clone();
+	let queue_status = document.get_element_by_id("queueStatus").unwrap();
+	let queue_status: web_sys::HtmlElement =
+		queue_status.dyn_into::<web_sys::HtmlElement>().map_err(|_| ()).unwrap();
+	let queue_status1 = queue_status.clone();
+	let mut key_frames = ws_receiver.filter_map(move |message| match message {
+		WsMessage::Binary(data) => match ServerMessage::deserialize(data.as_slice()) {
+			ServerMessage::View(view) => futures::future::ready(Some(RenderState::from(view))),
+			ServerMessage::Leaderboard(entries) => {
+				leaderboard1.borrow_mut().entries = entries;
+				futures::future::ready(None)
+			}
+			ServerMessage::GameOver(standings) => {
+				*results1.borrow_mut() = Some(standings);
+				futures::future::ready(None)
+			}
+			ServerMessage::RoundStart => {
+				*results1.borrow_mut() = None;
+				futures::future::ready(None)
+			}
+			ServerMessage::TeamScores(scores) => {
+				leaderboard1.borrow_mut().teams = scores;
+				futures::future::ready(None)
+			}
+			ServerMessage::Queued(position) => {
+				let text = format!("The arena is full. You are number {} in the queue.", position);
+				queue_status1.set_text_content(Some(&text));
+				queue_status1.style().set_property("display", "block").unwrap();
+				futures::future::ready(None)
+			}
+			ServerMessage::Map(map) => {
+				*arena1.borrow_mut() = Some(Rc::new(map));
+				futures::future::ready(None)
+			}
+		},
 		_ => futures::future::ready(None),
 	});
 
 	// Wait for two frames before rendering to allow interpolation.
 	let prev_frame = key_frames.next().await.unwrap();
 	let next_frame = key_frames.next().await.unwrap();
+	queue_status.style().set_property("display", "none").unwrap();
 	let mut stream = util::merge(
 		AnimationFrame::new(),
 		util::with_latest(key_frames, control_state_signal.to_stream()),
@@ -231,7 +348,22 @@ pub async fn start() {
 	while let Some(data) = stream.next().await {
 		match data {
 			// Start rendering if an animation frame is requested.
-			Either::Left(time) => interpolator.interpolate(time, &canvas).render(&mut piet_ctx),
+			Either::Left(time) => {
+				let mut view = interpolator.interpolate(time, &canvas);
+				if let Some(map) = arena.borrow().as_ref() {
+					view.set_arena(map.clone());
+				}
+				view.leaderboard = leaderboard.borrow().clone();
+				view.results = results.borrow().clone().map(|standings| Results {
+					pos: Position {
+						x: canvas.width() as f32 / 2.0,
+						y: canvas.height() as f32 / 2.0,
+					},
+					standings,
+					teams: leaderboard.borrow().teams.clone(),
+				});
+				view.render(&mut piet_ctx);
+			}
 			Either::Right((render_state, control)) => {
 				// Update the interpolator if a scene is received.
 				interpolator.update(perf.now(), render_state);
@@ -1,22 +1,110 @@
+use game_shared::shape::Shape as ObjectShape;
+use game_shared::shield::{
+	BashPhase, ShieldType, ARC_RADIUS, ARC_SPAN, ARC_THICKNESS, BULWARK_HALF_EXTENTS, LIGHT_RADIUS,
+	SPIKE_LENGTH, SPIKE_WIDTH,
+};
 use game_shared::{
-	CelestialView, PlayerView, Position, ShieldView, StaticView, ViewSnapshot, CELESTIAL_RADIUS,
-	INIT_RADIUS, MAP_HEIGHT, MAP_WIDTH, SHIELD_RADIUS, VIEW_X, VIEW_Y,
+	CelestialForm, CelestialView, MapView, PlayerView, Position, ScoreView, ShieldView, StaticView,
+	ViewSnapshot, INIT_RADIUS, MAP_HEIGHT, MAP_WIDTH, MAX_ENERGY, SHIELD_RADIUS, VIEW_X, VIEW_Y,
 };
-use piet::kurbo::{Circle, CircleSegment, Line, Rect, RoundedRect};
+use piet::kurbo::{Affine, Arc, BezPath, Circle, CircleSegment, Line, Rect, RoundedRect, Shape};
 use piet::{Color, RenderContext, Text, TextAttribute, TextLayout, TextLayoutBuilder};
 use piet_web::WebRenderContext;
 use std::cmp::{max, min};
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::time::Duration;
 
+const TEAM_COLORS: [(u8, u8, u8); 4] =
+	[(255, 110, 100), (128, 153, 255), (110, 220, 130), (240, 210, 90)];
+const TEAM_NAMES: [&str; 4] = ["Red", "Blue", "Green", "Yellow"];
+
+/// The color of players in `team`, or of players playing for themselves.
+fn team_color(team: Option<u32>) -> Color {
+	let (r, g, b) = match team {
+		Some(team) => TEAM_COLORS[team as usize % TEAM_COLORS.len()],
+		None => (128, 153, 255),
+	};
+	Color::rgb8(r, g, b)
+}
+
+/// The outline of a shield of `shield_type` in its own frame,
+/// with the body of its player along the positive x axis.
+fn shield_path(shield_type: ShieldType) -> BezPath {
+	match shield_type {
+		ShieldType::Circle => Circle::new((0.0, 0.0), SHIELD_RADIUS as f64).into_path(0.1),
+		ShieldType::WideArc => CircleSegment::new(
+			(ARC_RADIUS as f64, 0.0),
+			(ARC_RADIUS + ARC_THICKNESS / 2.0) as f64,
+			(ARC_RADIUS - ARC_THICKNESS / 2.0) as f64,
+			std::f64::consts::PI - ARC_SPAN as f64 / 2.0,
+			ARC_SPAN as f64,
+		)
+		.into_path(0.1),
+		ShieldType::Spike => {
+			let mut path = BezPath::new();
+			path.move_to((0.0, -SPIKE_WIDTH as f64 / 2.0));
+			path.line_to((0.0, SPIKE_WIDTH as f64 / 2.0));
+			path.line_to((-SPIKE_LENGTH as f64, 0.0));
+			path.close_path();
+			path
+		}
+		ShieldType::Bulwark => {
+			let (hx, hy) = (BULWARK_HALF_EXTENTS.0 as f64, BULWARK_HALF_EXTENTS.1 as f64);
+			Rect::new(-hx, -hy, hx, hy).into_path(0.1)
+		}
+		ShieldType::Light => Circle::new((0.0, 0.0), LIGHT_RADIUS as f64).into_path(0.1),
+	}
+}
+
+/// The outline of an object of `shape` centered at the origin.
+fn object_path(shape: &ObjectShape) -> BezPath {
+	match shape {
+		ObjectShape::Circle { radius } => Circle::new((0.0, 0.0), *radius as f64).into_path(0.1),
+		ObjectShape::Rect { half_width, half_height } => {
+			let (hx, hy) = (*half_width as f64, *half_height as f64);
+			Rect::new(-hx, -hy, hx, hy).into_path(0.1)
+		}
+		ObjectShape::Polygon { points } => {
+			let mut path = BezPath::new();
+			for (i, point) in points.iter().enumerate() {
+				let point = (point.x as f64, point.y as f64);
+				if i == 0 {
+					path.move_to(point);
+				} else {
+					path.line_to(point);
+				}
+			}
+			path.close_path();
+			path
+		}
+		ObjectShape::Capsule { half_length, radius } => {
+			let (hl, r) = (*half_length as f64, *radius as f64);
+			RoundedRect::new(-hl - r, -r, hl + r, r, r).into_path(0.1)
+		}
+	}
+}
+
+fn team_name(team: u32) -> String {
+	match TEAM_NAMES.get(team as usize) {
+		Some(name) => name.to_string(),
+		None => format!("Team {}", team + 1),
+	}
+}
+
 #[derive(Clone)]
 pub struct PlayerState {
 	pub name: String,
+	pub team: Option<u32>,
 	pub pos: Position,
 	pub ori: f32,
 	pub shield_pos: Position,
+	pub shield_rot: f32,
+	pub shield_type: ShieldType,
 	pub body_hp: i32,
 	pub shield_hp: i32,
+	pub shield_broken: bool,
+	pub shield_bash: BashPhase,
 }
 
 /// The scene ready for interpolation.
@@ -27,6 +115,7 @@ pub struct RenderState {
 	pub players: HashMap<u64, PlayerState>,
 	pub static_pos: HashMap<u64, StaticView>,
 	pub celestial_pos: HashMap<u64, CelestialView>,
+	pub energy: Option<f32>,
 }
 
 /// The interpolated scene ready for rendering.
@@ -37,10 +126,18 @@ pub struct FinalView {
 	/// Absolute position - offset = relative position
 	pub offset: Position,
 	pub self_pos: Position,
+	/// Scale of the world around the center of the canvas, 1 for the normal view.
+	pub zoom: f64,
 	pub players: Vec<PlayerState>,
 	pub static_pos: Vec<StaticView>,
 	pub celestial_pos: Vec<CelestialView>,
+	/// The walls and obstacles, once the map is known.
+	pub arena: Option<Arena>,
 	pub map: MiniMap,
+	pub leaderboard: Leaderboard,
+	pub results: Option<Results>,
+	pub replay: Option<ReplayBar>,
+	pub energy: Option<EnergyBar>,
 }
 
 impl From<ViewSnapshot> for RenderState {
@@ -59,11 +156,16 @@ impl From<ViewSnapshot> for RenderState {
 							id,
 							PlayerState {
 								name: player_view.name,
+								team: player_view.team,
 								pos: player_view.pos,
 								ori: player_view.ori,
 								shield_pos: shield_view.pos,
+								shield_rot: shield_view.rot,
+								shield_type: shield_view.shield_type,
 								body_hp: player_view.hp,
 								shield_hp: shield_view.hp,
+								shield_broken: shield_view.broken,
+								shield_bash: shield_view.bash,
 							},
 						)
 					})
@@ -71,6 +173,7 @@ impl From<ViewSnapshot> for RenderState {
 			},
 			static_pos: view.static_pos.into_iter().collect(),
 			celestial_pos: view.celestial_pos.into_iter().collect(),
+			energy: view.energy,
 		}
 	}
 }
@@ -86,7 +189,7 @@ impl Render for PlayerState {
 		let x = self.pos.x as f64;
 		let y = self.pos.y as f64;
 
-		let brush_fill = piet_ctx.solid_brush(Color::rgb8(128, 153, 255));
+		let brush_fill = piet_ctx.solid_brush(team_color(self.team));
 		let brush_stroke = piet_ctx.solid_brush(Color::rgb8(204, 214, 255));
 		let brush_shield_stroke = piet_ctx.solid_brush(Color::rgb8(242, 245, 255));
 		let brush_hp_bar_fill = piet_ctx.solid_brush(Color::from_hex_str("F75649").unwrap());
@@ -98,11 +201,42 @@ impl Render for PlayerState {
 		piet_ctx.stroke(&shape, &brush_stroke, 5.0);
 
 		// Render shield.
-		let x_shield = self.shield_pos.x;
-		let y_shield = self.shield_pos.y;
-		let shape = Circle::new((x_shield as f64, y_shield as f64), SHIELD_RADIUS as f64);
-		piet_ctx.fill(&shape, &brush_stroke);
-		piet_ctx.stroke(&shape, &brush_shield_stroke, 5.0);
+		let x_shield = self.shield_pos.x as f64;
+		let y_shield = self.shield_pos.y as f64;
+		let shape = shield_path(self.shield_type);
+		piet_ctx.save().unwrap();
+		piet_ctx.transform(
+			Affine::translate((x_shield, y_shield)) * Affine::rotate(self.shield_rot as f64),
+		);
+		if self.shield_broken {
+			// Broken shields let everything through until they regenerate.
+			let brush_broken = piet_ctx.solid_brush(Color::grey(0.4));
+			piet_ctx.stroke(&shape, &brush_broken, 2.0);
+		} else if self.shield_bash == BashPhase::Active {
+			// Bashing shields glow while they hit harder.
+			let brush_bash = piet_ctx.solid_brush(Color::rgb8(255, 190, 80));
+			piet_ctx.fill(&shape, &brush_stroke);
+			piet_ctx.stroke(&shape, &brush_bash, 8.0);
+		} else {
+			piet_ctx.fill(&shape, &brush_stroke);
+			piet_ctx.stroke(&shape, &brush_shield_stroke, 5.0);
+		}
+
+		// Render the HP of the shield as a ring around it.
+		let bounds = shape.bounding_box();
+		let radius = bounds.x0.abs().max(bounds.x1.abs()).max(bounds.y0.abs()).max(bounds.y1.abs());
+		let ring = Arc {
+			center: (0.0, 0.0).into(),
+			radii: (radius + 10.0, radius + 10.0).into(),
+			start_angle: -std::f64::consts::FRAC_PI_2,
+			sweep_angle: 2.0 * std::f64::consts::PI * self.shield_hp.max(0) as f64 / 100.0,
+			x_rotation: 0.0,
+		};
+		let ring_color =
+			if self.shield_broken { Color::rgb8(247, 86, 73) } else { Color::rgb8(120, 220, 255) };
+		let brush_ring = piet_ctx.solid_brush(ring_color);
+		piet_ctx.stroke(&ring, &brush_ring, 3.0);
+		piet_ctx.restore().unwrap();
 
 		// Render text.
 		let layout = piet_ctx
@@ -130,9 +264,12 @@ impl Render for StaticView {
 	/// Render non-player objects.
 	fn render(&self, piet_ctx: &mut WebRenderContext) {
 		let pt = (self.pos.x as f64, self.pos.y as f64);
-		let shape = Circle::new(pt, INIT_RADIUS as f64);
+		let shape = object_path(&self.shape);
 		let brush = piet_ctx.solid_brush(Color::grey(0.5));
+		piet_ctx.save().unwrap();
+		piet_ctx.transform(Affine::translate(pt) * Affine::rotate(self.rot as f64));
 		piet_ctx.fill(&shape, &brush);
+		piet_ctx.restore().unwrap();
 	}
 }
 
@@ -140,9 +277,40 @@ impl Render for CelestialView {
 	/// Render celestial bodies.
 	fn render(&self, piet_ctx: &mut WebRenderContext) {
 		let pt = (self.pos.x as f64, self.pos.y as f64);
-		let shape = Circle::new(pt, CELESTIAL_RADIUS as f64);
-		let brush = piet_ctx.solid_brush(Color::grey(1.0));
-		piet_ctx.fill(&shape, &brush);
+		let shape = Circle::new(pt, self.radius as f64);
+		match self.form {
+			CelestialForm::Planet => {
+				let brush = piet_ctx.solid_brush(Color::grey(1.0));
+				piet_ctx.fill(&shape, &brush);
+			}
+			CelestialForm::Sun => {
+				let brush = piet_ctx.solid_brush(Color::rgb8(255, 214, 90));
+				piet_ctx.fill(&shape, &brush);
+			}
+			CelestialForm::BlackHole => {
+				// A dark disk within the event horizon, ringed by glowing matter falling in.
+				let brush_glow = piet_ctx.solid_brush(Color::rgba8(255, 140, 60, 80));
+				let glow = Circle::new(pt, self.radius as f64 * 1.3);
+				piet_ctx.stroke(&glow, &brush_glow, self.radius as f64 * 0.3);
+				let brush_ring = piet_ctx.solid_brush(Color::rgb8(255, 170, 80));
+				piet_ctx.stroke(&shape, &brush_ring, 4.0);
+				let brush_hole = piet_ctx.solid_brush(Color::BLACK);
+				piet_ctx.fill(&shape, &brush_hole);
+			}
+		}
+
+		// Render the HP of damaged planets as a ring around them.
+		if self.form != CelestialForm::BlackHole && self.hp < 100 {
+			let ring = Arc {
+				center: pt.into(),
+				radii: (self.radius as f64 + 10.0, self.radius as f64 + 10.0).into(),
+				start_angle: -std::f64::consts::FRAC_PI_2,
+				sweep_angle: 2.0 * std::f64::consts::PI * self.hp.max(0) as f64 / 100.0,
+				x_rotation: 0.0,
+			};
+			let brush_ring = piet_ctx.solid_brush(Color::rgb8(247, 86, 73));
+			piet_ctx.stroke(&ring, &brush_ring, 3.0);
+		}
 	}
 }
 
@@ -151,23 +319,28 @@ struct Background {
 	abs_pos: Position,
 	offset_x: f64,
 	offset_y: f64,
+	zoom: f64,
+	/// Size of the map.
+	width: f32,
+	height: f32,
 }
 
 impl Render for Background {
 	fn render(&self, piet_ctx: &mut WebRenderContext) {
-		// TODO: highlight boundaries.
-		let left: f64 = (self.abs_pos.x - VIEW_X / 2.0) as f64;
-		let right: f64 = left + VIEW_X as f64;
-		let up: f64 = (self.abs_pos.y - VIEW_Y / 2.0) as f64;
-		let down: f64 = up + VIEW_Y as f64;
+		let view_x = VIEW_X as f64 / self.zoom;
+		let view_y = VIEW_Y as f64 / self.zoom;
+		let left: f64 = self.abs_pos.x as f64 - view_x / 2.0;
+		let right: f64 = left + view_x;
+		let up: f64 = self.abs_pos.y as f64 - view_y / 2.0;
+		let down: f64 = up + view_y;
 
 		let step_x = 100.0;
 		let step_y = 100.0;
 
 		let x_min = (left - left % step_x).max(0.0) - self.offset_x;
 		let y_min = (up - up % step_y).max(0.0) - self.offset_y;
-		let x_max = (right).min(MAP_WIDTH as f64) - self.offset_x;
-		let y_max = (down).min(MAP_HEIGHT as f64) - self.offset_y;
+		let x_max = (right).min(self.width as f64) - self.offset_x;
+		let y_max = (down).min(self.height as f64) - self.offset_y;
 
 		let brush = piet_ctx.solid_brush(Color::grey(0.5));
 
@@ -188,17 +361,84 @@ impl Render for Background {
 	}
 }
 
+/// The walls and obstacles of the map.
+pub struct Arena {
+	/// In absolute positions, shared with the frames before and after.
+	pub map: Rc<MapView>,
+	/// Absolute position - offset = relative position
+	pub offset: Position,
+}
+
+impl Render for Arena {
+	/// Render the walls and obstacles.
+	fn render(&self, piet_ctx: &mut WebRenderContext) {
+		let brush_wall = piet_ctx.solid_brush(Color::grey(0.85));
+		let brush_fill = piet_ctx.solid_brush(Color::grey(0.35));
+		piet_ctx.save().unwrap();
+		piet_ctx.transform(Affine::translate((-self.offset.x as f64, -self.offset.y as f64)));
+		for boundary in self.map.boundaries.iter() {
+			let mut path = BezPath::new();
+			for (i, point) in boundary.iter().enumerate() {
+				let point = (point.x as f64, point.y as f64);
+				if i == 0 {
+					path.move_to(point);
+				} else {
+					path.line_to(point);
+				}
+			}
+			piet_ctx.stroke(&path, &brush_wall, 4.0);
+		}
+		for obstacle in self.map.obstacles.iter() {
+			let shape = object_path(&obstacle.shape);
+			piet_ctx.save().unwrap();
+			piet_ctx.transform(
+				Affine::translate((obstacle.pos.x as f64, obstacle.pos.y as f64))
+					* Affine::rotate(obstacle.rot as f64),
+			);
+			piet_ctx.fill(&shape, &brush_fill);
+			piet_ctx.stroke(&shape, &brush_wall, 3.0);
+			piet_ctx.restore().unwrap();
+		}
+		piet_ctx.restore().unwrap();
+	}
+}
+
+impl FinalView {
+	/// Show the walls and obstacles of `map`, and fit the background and minimap to its size.
+	pub fn set_arena(&mut self, map: Rc<MapView>) {
+		self.map.size = Position { x: map.width, y: map.height };
+		self.arena = Some(Arena { map, offset: self.offset });
+	}
+}
+
 impl Render for FinalView {
 	/// Render the final scene.
 	fn render(&self, piet_ctx: &mut WebRenderContext) {
 		piet_ctx.clear(Color::rgb8(36, 39, 44));
 
+		// Scale the world around the center of the canvas, where the player is.
+		piet_ctx.save().unwrap();
+		let center = (
+			(self.self_abs_pos.x - self.offset.x) as f64,
+			(self.self_abs_pos.y - self.offset.y) as f64,
+		);
+		piet_ctx.transform(
+			Affine::translate(center)
+				* Affine::scale(self.zoom)
+				* Affine::translate((-center.0, -center.1)),
+		);
 		let bg = Background {
 			abs_pos: self.self_abs_pos,
 			offset_x: self.offset.x as f64,
 			offset_y: self.offset.y as f64,
+			zoom: self.zoom,
+			width: self.map.size.x,
+			height: self.map.size.y,
 		};
 		bg.render(piet_ctx);
+		if let Some(arena) = &self.arena {
+			arena.render(piet_ctx);
+		}
 
 		self.players.iter().for_each(|player_view| {
 			player_view.render(piet_ctx);
@@ -209,7 +449,18 @@ impl Render for FinalView {
 		self.celestial_pos.iter().for_each(|celestial_pos| {
 			celestial_pos.render(piet_ctx);
 		});
+		piet_ctx.restore().unwrap();
 		self.map.render(piet_ctx);
+		self.leaderboard.render(piet_ctx);
+		if let Some(results) = &self.results {
+			results.render(piet_ctx);
+		}
+		if let Some(replay) = &self.replay {
+			replay.render(piet_ctx);
+		}
+		if let Some(energy) = &self.energy {
+			energy.render(piet_ctx);
+		}
 
 		piet_ctx.finish().unwrap();
 	}
@@ -222,6 +473,8 @@ pub struct MiniMap {
 	pub self_pos: Position,
 	/// Celestial positions.
 	pub cele_views: Vec<CelestialView>,
+	/// Width and height of the map.
+	pub size: Position,
 }
 
 impl Render for MiniMap {
@@ -238,8 +491,8 @@ impl Render for MiniMap {
 
 		let shape = Circle::new(
 			(
-				map_x - len + (self.self_pos.x / MAP_WIDTH) as f64 * 2.0 * len,
-				map_y - len + (self.self_pos.y / MAP_HEIGHT) as f64 * 2.0 * len,
+				map_x - len + (self.self_pos.x / self.size.x) as f64 * 2.0 * len,
+				map_y - len + (self.self_pos.y / self.size.y) as f64 * 2.0 * len,
 			),
 			2.0,
 		);
@@ -249,8 +502,8 @@ impl Render for MiniMap {
 		for cele_view in self.cele_views.iter() {
 			let shape = Circle::new(
 				(
-					map_x - len + (cele_view.pos.x / MAP_WIDTH) as f64 * 2.0 * len,
-					map_y - len + (cele_view.pos.y / MAP_HEIGHT) as f64 * 2.0 * len,
+					map_x - len + (cele_view.pos.x / self.size.x) as f64 * 2.0 * len,
+					map_y - len + (cele_view.pos.y / self.size.y) as f64 * 2.0 * len,
 				),
 				4.0,
 			);
@@ -260,6 +513,203 @@ impl Render for MiniMap {
 	}
 }
 
+/// The top players, shown at the top-left corner, and the scores of the teams below.
+#[derive(Clone, Default)]
+pub struct Leaderboard {
+	pub entries: Vec<ScoreView>,
+	/// Total scores indexed by team, empty if not playing in teams.
+	pub teams: Vec<i32>,
+}
+
+impl Render for Leaderboard {
+	/// Render the leaderboard panel.
+	fn render(&self, piet_ctx: &mut WebRenderContext) {
+		if self.entries.is_empty() {
+			return;
+		}
+		render_scores(piet_ctx, "Leaderboard", &self.entries, 10.0, 10.0, 240.0);
+		let y = 30.0 + 24.0 * (self.entries.len() + 1) as f64;
+		render_team_scores(piet_ctx, &self.teams, 10.0, y, 240.0);
+	}
+}
+
+/// The final standings shown between rounds.
+#[derive(Clone)]
+pub struct Results {
+	/// Center position.
+	pub pos: Position,
+	pub standings: Vec<ScoreView>,
+	/// Total scores indexed by team, empty if not playing in teams.
+	pub teams: Vec<i32>,
+}
+
+impl Render for Results {
+	/// Render the results screen.
+	fn render(&self, piet_ctx: &mut WebRenderContext) {
+		let brush = piet_ctx.solid_brush(Color::rgba8(0, 0, 0, 96));
+		let shape = Rect::new(0.0, 0.0, 2.0 * self.pos.x as f64, 2.0 * self.pos.y as f64);
+		piet_ctx.fill(&shape, &brush);
+
+		let best_team = (0..self.teams.len()).max_by_key(|team| self.teams[*team]);
+		let title = match (best_team, self.standings.first()) {
+			(Some(team), _) => format!("{} team wins the round!", team_name(team as u32)),
+			(None, Some(winner)) => format!("{} wins the round!", winner.name),
+			(None, None) => "Round over".to_string(),
+		};
+		let width = 400.0;
+		let height = 24.0 * (self.standings.len() + 1) as f64 + 10.0;
+		let x = self.pos.x as f64 - width / 2.0;
+		let y = self.pos.y as f64 - height / 2.0;
+		render_scores(piet_ctx, &title, &self.standings, x, y, width);
+	}
+}
+
+/// The energy of the player for boosts.
+pub struct EnergyBar {
+	/// Center position.
+	pub pos: Position,
+	pub energy: f32,
+}
+
+impl Render for EnergyBar {
+	/// Render the energy bar at the bottom of the screen.
+	fn render(&self, piet_ctx: &mut WebRenderContext) {
+		let x = self.pos.x as f64;
+		let y = self.pos.y as f64;
+		let len = 150.0;
+		let height = 10.0;
+		let brush_fill = piet_ctx.solid_brush(Color::rgb8(250, 200, 60));
+		let brush_stroke = piet_ctx.solid_brush(Color::grey(0.9));
+		let fraction = (self.energy / MAX_ENERGY).max(0.0) as f64;
+		let shape =
+			RoundedRect::new(x - len, y, x - len + 2.0 * len * fraction, y + height, height / 2.0);
+		piet_ctx.fill(&shape, &brush_fill);
+		let shape = RoundedRect::new(x - len, y, x + len, y + height, height / 2.0);
+		piet_ctx.stroke(&shape, &brush_stroke, 2.0);
+	}
+}
+
+/// The progress and status of a replay being watched.
+pub struct ReplayBar {
+	/// Center position.
+	pub pos: Position,
+	/// Fraction of the replay played.
+	pub progress: f32,
+	pub text: String,
+}
+
+impl Render for ReplayBar {
+	/// Render the replay progress bar.
+	fn render(&self, piet_ctx: &mut WebRenderContext) {
+		let x = self.pos.x as f64;
+		let y = self.pos.y as f64;
+		let len = 300.0;
+		let height = 8.0;
+		let brush_fill = piet_ctx.solid_brush(Color::grey(0.9));
+		let brush_stroke = piet_ctx.solid_brush(Color::grey(0.5));
+		let shape = RoundedRect::new(
+			x - len,
+			y,
+			x - len + 2.0 * len * self.progress as f64,
+			y + height,
+			height / 2.0,
+		);
+		piet_ctx.fill(&shape, &brush_fill);
+		let shape = RoundedRect::new(x - len, y, x + len, y + height, height / 2.0);
+		piet_ctx.stroke(&shape, &brush_stroke, 2.0);
+
+		let layout = piet_ctx
+			.text()
+			.new_text_layout(self.text.clone())
+			.default_attribute(TextAttribute::FontSize(16.0))
+			.default_attribute(TextAttribute::TextColor(Color::grey(0.9)))
+			.build()
+			.unwrap();
+		piet_ctx.draw_text(&layout, (x - layout.size().width / 2.0, y - 24.0));
+	}
+}
+
+/// Render a panel of `entries` with a `title` at (`x`, `y`).
+fn render_scores(
+	piet_ctx: &mut WebRenderContext,
+	title: &str,
+	entries: &[ScoreView],
+	x: f64,
+	y: f64,
+	width: f64,
+) {
+	let row_height = 24.0;
+	let shape = Rect::new(x, y, x + width, y + row_height * (entries.len() + 1) as f64 + 10.0);
+	let brush = piet_ctx.solid_brush(Color::rgba8(0, 0, 0, 128));
+	piet_ctx.fill(&shape, &brush);
+
+	let title = piet_ctx
+		.text()
+		.new_text_layout(title.to_string())
+		.default_attribute(TextAttribute::FontSize(18.0))
+		.default_attribute(TextAttribute::TextColor(Color::grey(0.9)))
+		.build()
+		.unwrap();
+	piet_ctx.draw_text(&title, (x + (width - title.size().width) / 2.0, y + 5.0));
+
+	for (i, entry) in entries.iter().enumerate() {
+		let row_y = y + 5.0 + row_height * (i + 1) as f64;
+		let name = piet_ctx
+			.text()
+			.new_text_layout(format!("{}. {}", i + 1, entry.name))
+			.default_attribute(TextAttribute::FontSize(16.0))
+			.default_attribute(TextAttribute::TextColor(
+				entry.team.map_or(Color::grey(0.9), |team| team_color(Some(team))),
+			))
+			.build()
+			.unwrap();
+		piet_ctx.draw_text(&name, (x + 10.0, row_y));
+		let score = piet_ctx
+			.text()
+			.new_text_layout(format!(
+				"{} ({}/{}/{})",
+				entry.score, entry.kills, entry.deaths, entry.assists
+			))
+			.default_attribute(TextAttribute::FontSize(16.0))
+			.default_attribute(TextAttribute::TextColor(Color::grey(0.9)))
+			.build()
+			.unwrap();
+		piet_ctx.draw_text(&score, (x + width - 10.0 - score.size().width, row_y));
+	}
+}
+
+/// Render the total scores of `teams` at (`x`, `y`) in their colors.
+fn render_team_scores(piet_ctx: &mut WebRenderContext, teams: &[i32], x: f64, y: f64, width: f64) {
+	if teams.is_empty() {
+		return;
+	}
+	let row_height = 24.0;
+	let shape = Rect::new(x, y, x + width, y + row_height * teams.len() as f64 + 10.0);
+	let brush = piet_ctx.solid_brush(Color::rgba8(0, 0, 0, 128));
+	piet_ctx.fill(&shape, &brush);
+
+	for (team, score) in teams.iter().enumerate() {
+		let row_y = y + 5.0 + row_height * team as f64;
+		let color = team_color(Some(team as u32));
+		let name = piet_ctx
+			.text()
+			.new_text_layout(team_name(team as u32))
+			.default_attribute(TextAttribute::FontSize(16.0))
+			.default_attribute(TextAttribute::TextColor(color.clone()))
+			.build()
+			.unwrap();
+		piet_ctx.draw_text(&name, (x + 10.0, row_y));
+		let score = piet_ctx
+			.text()
+			.new_text_layout(score.to_string())
+			.default_attribute(TextAttribute::FontSize(16.0))
+			.default_attribute(TextAttribute::TextColor(color))
+			.build()
+			.unwrap();
+		piet_ctx.draw_text(&score, (x + width - 10.0 - score.size().width, row_y));
+	}
+}
+
 trait Interpolate: Sized {
 	type Output;
 
@@ -275,17 +725,29 @@ impl Interpolate for Position {
 	}
 }
 
+/// Interpolate between the angles `from` and `to` the shorter way around.
+fn interp_angle(from: f32, to: f32, t: f32) -> f32 {
+	let pi = std::f32::consts::PI;
+	let diff = (to - from + pi).rem_euclid(2.0 * pi) - pi;
+	from + t * diff
+}
+
 impl Interpolate for PlayerState {
 	type Output = PlayerState;
 
 	fn interp_with(&self, other: &PlayerState, t: f32) -> PlayerState {
 		PlayerState {
 			name: other.name.clone(),
+			team: other.team,
 			pos: self.pos.interp_with(&other.pos, t),
 			ori: other.ori,
 			shield_pos: self.shield_pos.interp_with(&other.shield_pos, t),
+			shield_rot: other.shield_rot,
+			shield_type: other.shield_type,
 			body_hp: other.body_hp,
 			shield_hp: other.shield_hp,
+			shield_broken: other.shield_broken,
+			shield_bash: other.shield_bash,
 		}
 	}
 }
@@ -294,7 +756,12 @@ impl Interpolate for StaticView {
 	type Output = StaticView;
 
 	fn interp_with(&self, other: &StaticView, t: f32) -> StaticView {
-		StaticView { pos: self.pos.interp_with(&other.pos, t), hp: other.hp }
+		StaticView {
+			pos: self.pos.interp_with(&other.pos, t),
+			rot: interp_angle(self.rot, other.rot, t),
+			shape: other.shape.clone(),
+			hp: other.hp,
+		}
 	}
 }
 
@@ -302,7 +769,12 @@ impl Interpolate for CelestialView {
 	type Output = CelestialView;
 
 	fn interp_with(&self, other: &CelestialView, t: f32) -> CelestialView {
-		CelestialView { pos: self.pos.interp_with(&other.pos, t), hp: other.hp }
+		CelestialView {
+			pos: self.pos.interp_with(&other.pos, t),
+			radius: other.radius,
+			form: other.form,
+			hp: other.hp,
+		}
 	}
 }
 
@@ -331,10 +803,21 @@ impl Interpolate for RenderState {
 			self_abs_pos: self_pos,
 			offset: Position::default(),
 			self_pos,
+			zoom: 1.0,
 			players: interp_items(&self.players, &other.players, t),
 			static_pos: interp_items(&self.static_pos, &other.static_pos, t),
 			celestial_pos: cele_views.clone(),
-			map: MiniMap { pos: self_pos, self_pos, cele_views },
+			arena: None,
+			map: MiniMap {
+				pos: self_pos,
+				self_pos,
+				cele_views,
+				size: Position { x: MAP_WIDTH, y: MAP_HEIGHT },
+			},
+			leaderboard: Leaderboard::default(),
+			results: None,
+			replay: None,
+			energy: other.energy.map(|energy| EnergyBar { pos: Position::default(), energy }),
 		}
 	}
 }
@@ -356,13 +839,26 @@ impl Interpolator {
 
 	/// Interpolate based on `time` and compute offsets based on the size of `canvas`.
 	pub fn interpolate(&self, time: f64, canvas: &web_sys::HtmlCanvasElement) -> FinalView {
-		let t = (self.base_time + time - self.prev.time.as_millis() as f64) as f32
+		self.interpolate_at(self.base_time + time, canvas)
+	}
+
+	/// Interpolate at `scene_time` (in milliseconds) of the frames instead of the local time.
+	pub fn interpolate_at(
+		&self,
+		scene_time: f64,
+		canvas: &web_sys::HtmlCanvasElement,
+	) -> FinalView {
+		let t = (scene_time - self.prev.time.as_millis() as f64) as f32
 			/ (self.next.time - self.prev.time).as_millis() as f32;
 		let mut view = self.prev.interp_with(&self.next, t);
 
 		// Compute positions relative to the canvas (centered at the player's position) before rendering.
 		view.map.pos =
 			Position { x: canvas.width() as f32 - 100.0, y: canvas.height() as f32 - 100.0 };
+		if let Some(energy) = &mut view.energy {
+			energy.pos =
+				Position { x: canvas.width() as f32 / 2.0, y: canvas.height() as f32 - 40.0 };
+		}
 		view.celestial_pos.iter().filter(|cele_view| {
 			(view.self_pos.x - cele_view.pos.x).abs() < VIEW_X
 				&& (view.self_pos.y - cele_view.pos.y).abs() < VIEW_Y
@@ -0,0 +1,281 @@
+use crate::render::{FinalView, Interpolator, Render, RenderState, ReplayBar};
+use crate::{util, AnimationFrame};
+use either::Either;
+use futures::StreamExt;
+use game_shared::replay::{Replay, ReplayRecord};
+use game_shared::{MapView, Position, ViewSnapshot, MAP_HEIGHT, MAP_WIDTH};
+use gloo::events::EventListener;
+use piet_web::WebRenderContext;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::KeyboardEvent;
+
+/// Milliseconds to skip when seeking with arrow keys.
+const SEEK_STEP: f64 = 5000.0;
+const MAX_SPEED: f64 = 16.0;
+const MIN_SPEED: f64 = 0.125;
+
+#[derive(Copy, Clone, Debug)]
+enum Control {
+	TogglePause,
+	Seek(f64),
+	Restart,
+	Faster,
+	Slower,
+	FollowNext,
+	Overview,
+}
+
+/// Plays recorded world snapshots back through the `Interpolator`.
+pub struct Playback {
+	snapshots: Vec<ViewSnapshot>,
+	/// The map played on, unknown in replays recorded before maps were.
+	map: Option<Rc<MapView>>,
+	/// Index of the snapshot right before `time`.
+	index: usize,
+	/// Current time of the recording in milliseconds.
+	time: f64,
+	speed: f64,
+	paused: bool,
+	/// The player the camera follows, or the whole map if there is none.
+	follow: Option<u64>,
+	interpolator: Interpolator,
+}
+
+impl Playback {
+	/// Prepare the world snapshots of `replay` for playback.
+	/// Returns `None` if there are too few snapshots to interpolate between.
+	pub fn new(replay: Replay) -> Option<Self> {
+		let mut map = None;
+		let mut snapshots = Vec::new();
+		for record in replay.records {
+			match record {
+				ReplayRecord::World(_, view) => snapshots.push(view),
+				ReplayRecord::Map(_, view) => map = Some(Rc::new(view)),
+				ReplayRecord::Events(..) => (),
+			}
+		}
+		if snapshots.len() < 2 {
+			return None;
+		}
+		let time = snapshots[0].time.as_millis() as f64;
+		let interpolator = Interpolator::new(
+			0.0,
+			RenderState::from(snapshots[0].clone()),
+			RenderState::from(snapshots[1].clone()),
+		);
+		Some(Playback {
+			snapshots,
+			map,
+			index: 0,
+			time,
+			speed: 1.0,
+			paused: false,
+			follow: None,
+			interpolator,
+		})
+	}
+
+	fn start_time(&self) -> f64 {
+		self.snapshots[0].time.as_millis() as f64
+	}
+
+	fn end_time(&self) -> f64 {
+		self.snapshots[self.snapshots.len() - 1].time.as_millis() as f64
+	}
+
+	/// The size of the map, or of the default map if it is unknown.
+	fn map_size(&self) -> Position {
+		match &self.map {
+			Some(map) => Position { x: map.width, y: map.height },
+			None => Position { x: MAP_WIDTH, y: MAP_HEIGHT },
+		}
+	}
+
+	/// Convert the snapshot at `index` with the camera on the followed player,
+	/// or on the center of the map if there is none.
+	fn frame(&self, index: usize) -> RenderState {
+		let mut state = RenderState::from(self.snapshots[index].clone());
+		let size = self.map_size();
+		state.self_pos = match self.follow.and_then(|id| state.players.get(&id)) {
+			Some(player) => player.pos,
+			None => Position { x: size.x / 2.0, y: size.y / 2.0 },
+		};
+		state
+	}
+
+	/// Rebuild the interpolator around the current time.
+	fn reload(&mut self) {
+		self.interpolator =
+			Interpolator::new(0.0, self.frame(self.index), self.frame(self.index + 1));
+	}
+
+	/// Advance the playback by `elapsed` milliseconds of local time.
+	fn advance(&mut self, elapsed: f64) {
+		if self.paused {
+			return;
+		}
+		self.time = (self.time + elapsed * self.speed).min(self.end_time());
+		while self.index + 2 < self.snapshots.len()
+			&& self.snapshots[self.index + 1].time.as_millis() as f64 <= self.time
+		{
+			self.index += 1;
+			let next = self.frame(self.index + 1);
+			self.interpolator.update(0.0, next);
+		}
+	}
+
+	fn seek(&mut self, time: f64) {
+		self.time = time.max(self.start_time()).min(self.end_time());
+		self.index = match self
+			.snapshots
+			.iter()
+			.position(|snapshot| snapshot.time.as_millis() as f64 > self.time)
+		{
+			Some(next) => next.max(1) - 1,
+			None => self.snapshots.len() - 2,
+		};
+		self.reload();
+	}
+
+	/// Follow the next player after the followed one, ordered by id.
+	fn follow_next(&mut self) {
+		let mut players: Vec<u64> =
+			self.snapshots[self.index].players.iter().map(|(id, _)| *id).collect();
+		players.sort();
+		self.follow = match self.follow {
+			Some(id) => players.iter().find(|other| **other > id).or(players.first()).copied(),
+			None => players.first().copied(),
+		};
+		self.reload();
+	}
+
+	fn control(&mut self, control: Control) {
+		match control {
+			Control::TogglePause => self.paused = !self.paused,
+			Control::Seek(step) => self.seek(self.time + step),
+			Control::Restart => self.seek(self.start_time()),
+			Control::Faster => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+			Control::Slower => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+			Control::FollowNext => self.follow_next(),
+			Control::Overview => {
+				self.follow = None;
+				self.reload();
+			}
+		}
+	}
+
+	/// The scene at the current time along with the playback status.
+	fn view(&self, canvas: &web_sys::HtmlCanvasElement) -> FinalView {
+		let mut view = self.interpolator.interpolate_at(self.time, canvas);
+		if let Some(map) = &self.map {
+			view.set_arena(map.clone());
+		}
+		if self.follow.is_none() {
+			// Fit the whole map into the canvas.
+			let size = self.map_size();
+			view.zoom =
+				(canvas.width() as f64 / size.x as f64).min(canvas.height() as f64 / size.y as f64);
+		}
+		let following = self
+			.follow
+			.and_then(|id| {
+				self.snapshots[self.index].players.iter().find(|(other, _)| *other == id)
+			})
+			.map_or("map".to_string(), |(_, player)| player.name.clone());
+		view.replay = Some(ReplayBar {
+			pos: Position { x: canvas.width() as f32 / 2.0, y: canvas.height() as f32 - 40.0 },
+			progress: ((self.time - self.start_time()) / (self.end_time() - self.start_time()))
+				as f32,
+			text: format!(
+				"{} / {}  x{}{}  following {}",
+				format_time(self.time - self.start_time()),
+				format_time(self.end_time() - self.start_time()),
+				self.speed,
+				if self.paused { "  paused" } else { "" },
+				following
+			),
+		});
+		view
+	}
+}
+
+/// Read the whole content of `blob`, e.g. a file picked by the user.
+pub async fn read_blob(blob: &web_sys::Blob) -> Vec<u8> {
+	let buffer = JsFuture::from(blob.array_buffer()).await.expect("Failed to read the file.");
+	js_sys::Uint8Array::new(&buffer).to_vec()
+}
+
+/// Download the content at `url`.
+pub async fn fetch(url: &str) -> Vec<u8> {
+	let window = web_sys::window().expect("Window doesn't exist.");
+	let response: web_sys::Response = JsFuture::from(window.fetch_with_str(url))
+		.await
+		.expect("Failed to fetch.")
+		.dyn_into()
+		.unwrap();
+	let buffer = JsFuture::from(response.array_buffer().unwrap())
+		.await
+		.expect("Failed to read the response.");
+	js_sys::Uint8Array::new(&buffer).to_vec()
+}
+
+fn format_time(millis: f64) -> String {
+	let secs = (millis / 1000.0) as u64;
+	format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// Play `replay` back on `canvas` until the page is closed.
+///
+/// Space pauses, left and right arrows seek, up and down arrows change the speed,
+/// Home restarts, Tab follows the next player and Escape shows the whole map.
+pub async fn watch(
+	replay: Replay,
+	canvas: &web_sys::HtmlCanvasElement,
+	piet_ctx: &mut WebRenderContext,
+) {
+	let mut playback = match Playback::new(replay) {
+		Some(playback) => playback,
+		None => {
+			web_sys::console::log_1(&"The replay has no world snapshots to play.".into());
+			return;
+		}
+	};
+
+	let document = web_sys::window().unwrap().document().unwrap();
+	let (control_sender, controls) = futures::channel::mpsc::unbounded();
+	EventListener::new(&document, "keydown", move |event| {
+		let event: &KeyboardEvent = event.dyn_ref().unwrap_throw();
+		let control = match event.code().as_ref() {
+			"Space" => Control::TogglePause,
+			"ArrowLeft" => Control::Seek(-SEEK_STEP),
+			"ArrowRight" => Control::Seek(SEEK_STEP),
+			"ArrowUp" => Control::Faster,
+			"ArrowDown" => Control::Slower,
+			"Home" => Control::Restart,
+			"Tab" => Control::FollowNext,
+			"Escape" => Control::Overview,
+			_ => return,
+		};
+		event.prevent_default();
+		control_sender.unbounded_send(control).unwrap();
+	})
+	.forget();
+
+	let mut stream = util::merge(AnimationFrame::new(), controls);
+	let mut last_time = None;
+	while let Some(data) = stream.next().await {
+		match data {
+			Either::Left(time) => {
+				if let Some(last_time) = last_time {
+					playback.advance(time - last_time);
+				}
+				last_time = Some(time);
+				playback.view(canvas).render(piet_ctx);
+			}
+			Either::Right(control) => playback.control(control),
+		}
+	}
+}
@@ -0,0 +1,52 @@
+use bevy::math::Vec2;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use game_server::barnes_hut::QuadTree;
+use game_server::gravity::{acceleration, Attractor};
+use game_shared::{MAP_HEIGHT, MAP_WIDTH};
+
+const GRAVITY_CONST: f32 = 20.0;
+/// Bodies pulled by the attractors in every run.
+const BODIES: usize = 1000;
+
+fn random_point(rng: &mut StdRng) -> Vec2 {
+	Vec2::new(rng.gen_range(0.0..MAP_WIDTH), rng.gen_range(0.0..MAP_HEIGHT))
+}
+
+/// The sum of `accels`, so that computing them isn't optimized away.
+fn total(accels: impl Iterator<Item = Vec2>) -> Vec2 {
+	accels.fold(Vec2::ZERO, |total, accel| total + accel)
+}
+
+/// Pull `BODIES` bodies towards growing numbers of attractors, exactly and with quadtrees.
+fn pull(c: &mut Criterion) {
+	let mut rng = StdRng::seed_from_u64(0);
+	let bodies: Vec<Vec2> = (0..BODIES).map(|_| random_point(&mut rng)).collect();
+	let mut group = c.benchmark_group("pull");
+	for &count in [10, 100, 1000, 10000].iter() {
+		let attractors: Vec<Attractor> = (0..count)
+			.map(|_| Attractor { pos: random_point(&mut rng), mass: rng.gen_range(1e5..1e7) })
+			.collect();
+		let id = BenchmarkId::new("brute_force", count);
+		group.bench_with_input(id, &attractors, |b, attractors| {
+			b.iter(|| total(bodies.iter().map(|pos| acceleration(*pos, attractors, GRAVITY_CONST))))
+		});
+		for &opening_angle in [0.5, 1.0].iter() {
+			let id = BenchmarkId::new(format!("barnes_hut_{}", opening_angle), count);
+			group.bench_with_input(id, &attractors, |b, attractors| {
+				b.iter(|| {
+					// Building the tree is part of every tick.
+					let tree = QuadTree::new(attractors);
+					let pull = |pos: &Vec2| tree.acceleration(*pos, GRAVITY_CONST, opening_angle);
+					total(bodies.iter().map(pull))
+				})
+			});
+		}
+	}
+	group.finish();
+}
+
+criterion_group!(benches, pull);
+criterion_main!(benches);
@@ -0,0 +1,147 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_rapier2d::physics::JointHandleComponent;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use game_shared::{PlayerState, INIT_RADIUS, VIEW_X};
+
+use crate::component::{Player, ShieldID, ShieldType, HP};
+use crate::config::Config;
+use crate::map::Map;
+use crate::server::{GameRng, GameServer};
+use crate::system::{despawn_player, pick_team, spawn_player, team_sizes, ChangeMovement};
+
+/// Bots flee from other players below this HP.
+const FLEE_HP: i32 = 30;
+/// Distance within which bots ram other players with their shields pushed.
+const RAM_RANGE: f32 = 10.0 * INIT_RADIUS;
+/// Chance per tick that a wandering bot picks a new direction.
+const WANDER_CHANCE: f64 = 1.0 / 120.0;
+
+/// What an AI-controlled player does about the nearest other player.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Behavior {
+	/// Nobody is in sight, so move around looking for someone.
+	Wander,
+	/// Move towards the player with the shield in front.
+	Chase,
+	/// Boost into the player with the shield pushed out, bashing it.
+	Ram,
+	/// Move away from the player while covering behind with the shield.
+	Flee,
+}
+
+/// An AI-controlled player, which has no session.
+pub struct Bot {
+	pub behavior: Behavior,
+	/// The direction the bot moves towards while wandering.
+	wander_dir: f32,
+}
+
+/// Add or remove bots so that there are `Config::min_population` players in the arena.
+pub fn maintain_population(
+	mut commands: Commands,
+	config: Res<Config>,
+	map: Res<Map>,
+	game_state: Res<GameServer>,
+	mut rng: ResMut<GameRng>,
+	mut spawned: Local<u32>,
+	bots: Query<Entity, With<Bot>>,
+	shield_ids: Query<&ShieldID>,
+	joints: Query<(Entity, &JointHandleComponent)>,
+	players: Query<&Player>,
+) {
+	let wanted = config.min_population.saturating_sub(game_state.sessions.len());
+	let count = bots.iter().count();
+	if count < wanted {
+		let mut team_sizes = team_sizes(config.teams, &players);
+		for _ in count..wanted {
+			*spawned += 1;
+			let name = format!("Bot {}", *spawned);
+			let team = pick_team(None, &mut team_sizes);
+			let shield_type = *ShieldType::ALL.choose(&mut rng.0).unwrap();
+			let player = Player { name, team };
+			let player =
+				spawn_player(&mut commands, player, shield_type, &mut rng.0, &config, &map);
+			let wander_dir = rng.0.gen_range(-PI..PI);
+			commands.entity(player).insert(Bot { behavior: Behavior::Wander, wander_dir });
+		}
+	} else {
+		for bot in bots.iter().take(count - wanted) {
+			despawn_player(&mut commands, bot, &shield_ids, &joints);
+		}
+	}
+}
+
+/// Steer bots with the same inputs as human players, going after players of other teams.
+pub fn drive_bots(
+	mut commands: Commands,
+	map: Res<Map>,
+	mut rng: ResMut<GameRng>,
+	mut bots: Query<(Entity, &mut Bot, &Player, &Transform, &HP)>,
+	players: Query<(Entity, &Player, &Transform)>,
+) {
+	for (entity, mut bot, player, transform, hp) in bots.iter_mut() {
+		let nearest = players
+			.iter()
+			.filter(|(other, other_player, _)| {
+				*other != entity && (player.team.is_none() || other_player.team != player.team)
+			})
+			.map(|(_, _, other)| other.translation - transform.translation)
+			.filter(|disp| disp.length() < VIEW_X)
+			.min_by(|a, b| a.length().partial_cmp(&b.length()).unwrap());
+
+		bot.behavior = match nearest {
+			None => Behavior::Wander,
+			Some(_) if hp.val < FLEE_HP => Behavior::Flee,
+			Some(disp) if disp.length() < RAM_RANGE => Behavior::Ram,
+			Some(_) => Behavior::Chase,
+		};
+		let towards = nearest.map_or(0.0, |disp| disp.y.atan2(disp.x));
+		let state = match bot.behavior {
+			Behavior::Wander => {
+				if rng.0.gen_bool(WANDER_CHANCE) {
+					bot.wander_dir = wander_dir(&map, transform.translation, &mut rng);
+				}
+				let dir = Some(bot.wander_dir);
+				let ori = bot.wander_dir;
+				PlayerState { dir, ori, push_shield: false, boost: false, bash: false }
+			}
+			Behavior::Chase => PlayerState {
+				dir: Some(towards),
+				ori: towards,
+				push_shield: false,
+				boost: false,
+				bash: false,
+			},
+			Behavior::Ram => PlayerState {
+				dir: Some(towards),
+				ori: towards,
+				push_shield: true,
+				boost: true,
+				bash: true,
+			},
+			Behavior::Flee => PlayerState {
+				dir: Some(towards + PI),
+				ori: towards,
+				push_shield: false,
+				boost: false,
+				bash: false,
+			},
+		};
+		commands.add(ChangeMovement { player: entity, state });
+	}
+}
+
+/// Pick a random direction, turning towards the center of the map near its boundary.
+fn wander_dir(map: &Map, pos: Vec3, rng: &mut GameRng) -> f32 {
+	let to_center = Vec2::new(map.width / 2.0 - pos.x, map.height / 2.0 - pos.y);
+	let angle = to_center.y.atan2(to_center.x);
+	if to_center.x.abs() > 0.4 * map.width || to_center.y.abs() > 0.4 * map.height {
+		angle + rng.0.gen_range(-PI / 4.0..PI / 4.0)
+	} else {
+		rng.0.gen_range(-PI..PI)
+	}
+}
@@ -0,0 +1,104 @@
+use bevy::math::Vec2;
+
+use crate::gravity::Attractor;
+
+/// Cells are split this many times at most, so that attractors at the same spot end up in
+/// one leaf instead of being split forever.
+const MAX_DEPTH: usize = 24;
+
+/// A quadtree of attractors, pulling bodies far from a cell by the total mass of the cell
+/// at its center of mass, which takes O(log n) instead of O(n) per body.
+pub struct QuadTree {
+	nodes: Vec<Node>,
+}
+
+struct Node {
+	/// Total mass and center of mass of the attractors in the cell.
+	mass: f32,
+	com: Vec2,
+	/// Side length of the square cell.
+	size: f32,
+	/// Indices of the children covering the non-empty quarters of the cell, none for leaves.
+	children: Vec<usize>,
+}
+
+impl QuadTree {
+	pub fn new(attractors: &[Attractor]) -> Self {
+		let mut tree = QuadTree { nodes: Vec::new() };
+		if attractors.is_empty() {
+			return tree;
+		}
+		let (min, max) = attractors.iter().fold(
+			(Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+			|(min, max), attractor| (min.min(attractor.pos), max.max(attractor.pos)),
+		);
+		let size = (max - min).max_element();
+		tree.build(attractors.to_vec(), (min + max) / 2.0, size, 0);
+		tree
+	}
+
+	/// Add the cell centered at `center` with the side `size` holding `attractors`,
+	/// returning its index.
+	fn build(
+		&mut self,
+		attractors: Vec<Attractor>,
+		center: Vec2,
+		size: f32,
+		depth: usize,
+	) -> usize {
+		let mass: f32 = attractors.iter().map(|attractor| attractor.mass).sum();
+		let com = if mass > 0.0 {
+			attractors
+				.iter()
+				.fold(Vec2::ZERO, |com, attractor| com + attractor.pos * attractor.mass)
+				/ mass
+		} else {
+			center
+		};
+		let index = self.nodes.len();
+		self.nodes.push(Node { mass, com, size, children: Vec::new() });
+		if attractors.len() <= 1 || depth == MAX_DEPTH {
+			return index;
+		}
+
+		let mut quarters: [Vec<Attractor>; 4] = Default::default();
+		for attractor in attractors {
+			let right = (attractor.pos.x >= center.x) as usize;
+			let top = (attractor.pos.y >= center.y) as usize;
+			quarters[2 * top + right].push(attractor);
+		}
+		for (i, quarter) in quarters.iter_mut().enumerate() {
+			if quarter.is_empty() {
+				continue;
+			}
+			let sign =
+				Vec2::new(if i % 2 == 1 { 1.0 } else { -1.0 }, if i >= 2 { 1.0 } else { -1.0 });
+			let quarter = std::mem::take(quarter);
+			let child = self.build(quarter, center + sign * size / 4.0, size / 2.0, depth + 1);
+			self.nodes[index].children.push(child);
+		}
+		index
+	}
+
+	/// The gravitational acceleration of a body at `pos` towards the attractors.
+	/// Cells seen under an angle below `opening_angle` pull as a whole, and an angle of 0 gives
+	/// the exact pull of every attractor.
+	pub fn acceleration(&self, pos: Vec2, gravity_const: f32, opening_angle: f32) -> Vec2 {
+		let mut accel = Vec2::ZERO;
+		let mut stack = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+		while let Some(index) = stack.pop() {
+			let node = &self.nodes[index];
+			let disp = node.com - pos;
+			let dist = disp.length();
+			if node.children.is_empty() || node.size < opening_angle * dist {
+				// Attractors right at `pos`, like the body itself, don't pull it.
+				if dist > 0.0 {
+					accel += gravity_const * node.mass * disp / dist.powi(3);
+				}
+			} else {
+				stack.extend(node.children.iter().copied());
+			}
+		}
+		accel
+	}
+}
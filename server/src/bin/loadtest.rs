@@ -0,0 +1,203 @@
+//! Open many concurrent connections to a running server, play with random inputs the way
+//! the browser client does, and report how well the server keeps up.
+//!
+//! Usage: `loadtest CLIENTS SECONDS [URL]`
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use actix_web::rt::time::{delay_for, timeout};
+use actix_web::web::Bytes;
+use awc::ws::{Frame, Message};
+use futures::{SinkExt, StreamExt};
+use rand::rngs::ThreadRng;
+use rand::Rng;
+
+use game_shared::{JoinInfo, Operation, PlayerState, ServerMessage};
+
+use game_server::TICK_TIME;
+
+const DEFAULT_URL: &str = "ws://127.0.0.1:8080/ws";
+/// Delay between opening consecutive connections.
+const RAMP_UP_STEP: Duration = Duration::from_millis(10);
+/// Snapshots of hundreds of players exceed the default frame size limit.
+const MAX_FRAME_SIZE: usize = 1 << 24;
+/// Chance that a client changes its input on a snapshot, about every two seconds.
+const CHANGE_CHANCE: f64 = 1.0 / 120.0;
+/// Time given to the clients to finish after the run is over.
+const GRACE_TIME: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+struct Stats {
+	connected: usize,
+	failed: usize,
+	disconnected: usize,
+	messages: u64,
+	bytes: u64,
+	snapshots: u64,
+	updates: u64,
+	/// Milliseconds between the server times of consecutive snapshots.
+	tick_gaps: Vec<f64>,
+	/// Milliseconds each snapshot took longer to arrive than the fastest one of its connection,
+	/// as the clocks of the server and the clients are not synchronized.
+	latencies: Vec<f64>,
+}
+
+#[actix_web::main]
+async fn main() {
+	let args: Vec<String> = std::env::args().collect();
+	if args.len() < 3 {
+		eprintln!("Usage: {} CLIENTS SECONDS [URL]", args[0]);
+		std::process::exit(1);
+	}
+	let clients: usize = args[1].parse().expect("CLIENTS should be a number.");
+	let seconds: u64 = args[2].parse().expect("SECONDS should be a number.");
+	let url = args.get(3).map_or(DEFAULT_URL, String::as_str);
+
+	let start = Instant::now();
+	let deadline = start + Duration::from_secs(seconds);
+	let stats = Rc::new(RefCell::new(Stats::default()));
+	let runs = (0..clients).map(|id| run_client(id, url, start, deadline, stats.clone()));
+	let all_done = futures::future::join_all(runs);
+	if timeout(deadline - start + GRACE_TIME, all_done).await.is_err() {
+		eprintln!("Some clients didn't finish in time.");
+	}
+
+	let mut stats = stats.borrow_mut();
+	let secs = seconds as f64;
+	println!(
+		"{} clients for {} s: {} connected, {} failed to connect, {} disconnected early",
+		clients, seconds, stats.connected, stats.failed, stats.disconnected
+	);
+	println!(
+		"Received {} messages ({:.1}/s) of {:.1} KiB ({:.1} KiB/s, {:.1} KiB/s per client)",
+		stats.messages,
+		stats.messages as f64 / secs,
+		stats.bytes as f64 / 1024.0,
+		stats.bytes as f64 / 1024.0 / secs,
+		stats.bytes as f64 / 1024.0 / secs / stats.connected.max(1) as f64,
+	);
+	println!(
+		"Received {} snapshots ({:.1}/s per client, {:.1}/s expected), sent {} updates",
+		stats.snapshots,
+		stats.snapshots as f64 / secs / stats.connected.max(1) as f64,
+		1.0 / TICK_TIME.as_secs_f64(),
+		stats.updates,
+	);
+	println!("Snapshot latency: {}", summarize(&mut stats.latencies));
+	let late = stats.tick_gaps.iter().filter(|gap| **gap > 2.0 * millis(TICK_TIME)).count();
+	println!(
+		"Server time between snapshots: {}, {:.1}% late by over a tick",
+		summarize(&mut stats.tick_gaps),
+		100.0 * late as f64 / stats.tick_gaps.len().max(1) as f64,
+	);
+}
+
+/// Join the game and answer every snapshot with an input until `deadline`.
+async fn run_client(
+	id: usize,
+	url: &str,
+	start: Instant,
+	deadline: Instant,
+	stats: Rc<RefCell<Stats>>,
+) {
+	delay_for(RAMP_UP_STEP * id as u32).await;
+	let connection = awc::Client::new().ws(url).max_frame_size(MAX_FRAME_SIZE).connect().await;
+	let (_, framed) = match connection {
+		Ok(connection) => connection,
+		Err(err) => {
+			eprintln!("Client {} failed to connect: {}", id, err);
+			stats.borrow_mut().failed += 1;
+			return;
+		}
+	};
+	stats.borrow_mut().connected += 1;
+	let (mut sink, mut stream) = framed.split();
+
+	let mut rng = rand::thread_rng();
+	let mut state = random_state(&mut rng);
+	let mut last_time = None;
+	let mut delays = Vec::new();
+	let info = JoinInfo { name: format!("load-{}", id), ..Default::default() };
+	let join = Operation::Join(info).serialize();
+	let mut alive = sink.send(Message::Binary(Bytes::from(join))).await.is_ok();
+	while alive && Instant::now() < deadline {
+		let bin = match stream.next().await {
+			Some(Ok(Frame::Binary(bin))) => bin,
+			Some(Ok(Frame::Ping(msg))) => {
+				alive = sink.send(Message::Pong(msg)).await.is_ok();
+				continue;
+			}
+			Some(Ok(Frame::Close(_))) | Some(Err(_)) | None => break,
+			Some(Ok(_)) => continue,
+		};
+		let received = Instant::now();
+		{
+			let mut stats = stats.borrow_mut();
+			stats.messages += 1;
+			stats.bytes += bin.len() as u64;
+		}
+		let view = match ServerMessage::deserialize(&bin) {
+			ServerMessage::View(view) => view,
+			_ => continue,
+		};
+		{
+			let mut stats = stats.borrow_mut();
+			stats.snapshots += 1;
+			if let Some(last_time) = last_time {
+				stats.tick_gaps.push(millis(view.time.checked_sub(last_time).unwrap_or_default()));
+			}
+		}
+		last_time = Some(view.time);
+		// Includes the difference of the clocks, which is the same for every snapshot.
+		delays.push(millis(received - start) - millis(view.time));
+
+		if rng.gen_bool(CHANGE_CHANCE) {
+			state = random_state(&mut rng);
+		}
+		let update = Operation::Update(state.clone()).serialize();
+		alive = sink.send(Message::Binary(Bytes::from(update))).await.is_ok();
+		stats.borrow_mut().updates += 1;
+	}
+
+	let mut stats = stats.borrow_mut();
+	if Instant::now() < deadline {
+		stats.disconnected += 1;
+	}
+	let fastest = delays.iter().copied().fold(f64::INFINITY, f64::min);
+	stats.latencies.extend(delays.iter().map(|delay| delay - fastest));
+	drop(stats);
+	let _ = sink.send(Message::Close(None)).await;
+}
+
+/// Move in a random direction, or stand still, and aim somewhere random.
+fn random_state(rng: &mut ThreadRng) -> PlayerState {
+	let dir = rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI);
+	PlayerState {
+		dir: if rng.gen_bool(0.8) { Some(dir) } else { None },
+		ori: rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI),
+		push_shield: rng.gen_bool(0.3),
+		boost: rng.gen_bool(0.1),
+		bash: rng.gen_bool(0.05),
+	}
+}
+
+fn millis(duration: Duration) -> f64 {
+	duration.as_secs_f64() * 1000.0
+}
+
+/// The median, 99th percentile and maximum of `values` in milliseconds.
+fn summarize(values: &mut [f64]) -> String {
+	if values.is_empty() {
+		return "no samples".to_string();
+	}
+	values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	let percentile = |p: f64| values[((values.len() - 1) as f64 * p) as usize];
+	format!(
+		"p50 {:.1} ms, p99 {:.1} ms, max {:.1} ms",
+		percentile(0.5),
+		percentile(0.99),
+		values[values.len() - 1]
+	)
+}
@@ -0,0 +1,204 @@
+//! Run the game world without networking at full speed with scripted players,
+//! and print statistics of the run as JSON for comparing tuning parameters.
+//!
+//! Usage: `simulate PLAYERS TICKS [CONFIG]`
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::MinimalPlugins;
+use bevy_rapier2d::physics::RapierPhysicsPlugin;
+use bevy_rapier2d::prelude::NoUserData;
+use bevy_rapier2d::rapier::dynamics::RigidBodyVelocity;
+use rand::Rng;
+use serde::Serialize;
+
+use game_shared::PlayerState;
+
+use game_server::component::{CelestialBody, Player, Score, ShieldType};
+use game_server::config::{Config, Tuning, WinCondition};
+use game_server::event::EventListener;
+use game_server::map::Map;
+use game_server::server::GameRng;
+use game_server::system::{spawn_player, ChangeMovement};
+use game_server::{build_game, Tick, TickStage, TICK_TIME};
+
+/// Distance from the center of a planet, in its radii, within which a player counts as near it.
+const NEAR_PLANET: f32 = 5.0;
+/// Distance within which scripted players push their shields towards others.
+const PUSH_RANGE: f32 = 150.0;
+
+struct Simulation {
+	players: usize,
+	ticks: u64,
+	tick: u64,
+}
+
+/// A scripted player wandering in `dir`.
+struct Bot {
+	dir: Option<f32>,
+}
+
+/// Statistics accumulated over the ticks.
+#[derive(Default)]
+struct Track {
+	ticks_near_planets: u64,
+	speed_sum: f64,
+}
+
+#[derive(Serialize)]
+struct PlayerReport {
+	name: String,
+	damage_dealt: i32,
+	kills: u32,
+	deaths: u32,
+	time_near_planets: f64,
+	average_speed: f64,
+}
+
+#[derive(Serialize)]
+struct Report {
+	players: usize,
+	ticks: u64,
+	seed: u64,
+	tuning: Tuning,
+	total_damage_dealt: i32,
+	total_deaths: u32,
+	average_time_near_planets: f64,
+	average_speed: f64,
+	per_player: Vec<PlayerReport>,
+}
+
+fn main() {
+	let args: Vec<String> = std::env::args().collect();
+	if args.len() < 3 {
+		eprintln!("Usage: {} PLAYERS TICKS [CONFIG]", args[0]);
+		std::process::exit(1);
+	}
+	let players = args[1].parse().expect("PLAYERS should be a number.");
+	let ticks = args[2].parse().expect("TICKS should be a number.");
+	let mut config = Config::load(args.get(3).cloned());
+	// Rounds never end in the simulation.
+	config.round_secs = u64::MAX;
+	config.win_condition = WinCondition::TimeLimit;
+	config.replay_path = None;
+	// Only the scripted players take part.
+	config.min_population = 0;
+	config.seed.get_or_insert_with(rand::random);
+
+	// Nobody ever joins through the network, but the channel has to stay open for the game
+	// to poll it.
+	let (_sender, receiver) = futures::channel::mpsc::unbounded();
+	let mut app = App::build();
+	app.add_plugins(MinimalPlugins).add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+	build_game(&mut app, config, EventListener(receiver));
+	app.insert_resource(Simulation { players, ticks, tick: 0 })
+		.add_startup_system(spawn_bots.system())
+		// After the game in the same order every tick, so that the seed reproduces the run.
+		.add_system_set_to_stage(
+			TickStage,
+			SystemSet::new()
+				.with_system(drive_bots.system().label("script").after(Tick::Record))
+				.with_system(track_players.system().after("script")),
+		)
+		.run();
+}
+
+fn spawn_bots(
+	mut commands: Commands,
+	simulation: Res<Simulation>,
+	config: Res<Config>,
+	map: Res<Map>,
+	mut rng: ResMut<GameRng>,
+) {
+	for i in 0..simulation.players {
+		let name = format!("Bot {}", i);
+		let team = (config.teams > 0).then(|| i as u32 % config.teams);
+		let player = Player { name, team };
+		let shield_type = ShieldType::Circle;
+		let player = spawn_player(&mut commands, player, shield_type, &mut rng.0, &config, &map);
+		commands.entity(player).insert(Bot { dir: None }).insert(Track::default());
+	}
+}
+
+/// Wander around, turn the shield towards the nearest player and push it when close.
+fn drive_bots(
+	mut commands: Commands,
+	mut rng: ResMut<GameRng>,
+	mut bots: Query<(Entity, &Transform, &mut Bot)>,
+) {
+	let rng = &mut rng.0;
+	let positions: Vec<(Entity, Vec3)> =
+		bots.iter_mut().map(|(entity, transform, _)| (entity, transform.translation)).collect();
+	for (entity, transform, mut bot) in bots.iter_mut() {
+		if rng.gen_range(0..120) == 0 {
+			bot.dir = if rng.gen_bool(0.8) {
+				Some(rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI))
+			} else {
+				None
+			};
+		}
+		let nearest = positions
+			.iter()
+			.filter(|(other, _)| *other != entity)
+			.map(|(_, pos)| *pos - transform.translation)
+			.min_by(|a, b| a.length().partial_cmp(&b.length()).unwrap());
+		let (ori, push_shield) = match nearest {
+			Some(disp) => (disp.y.atan2(disp.x), disp.length() < PUSH_RANGE),
+			None => (0.0, false),
+		};
+		let state = PlayerState { dir: bot.dir, ori, push_shield, boost: false, bash: false };
+		commands.add(ChangeMovement::new(entity, state));
+	}
+}
+
+/// Accumulate statistics of every player and report them after the last tick.
+fn track_players(
+	mut simulation: ResMut<Simulation>,
+	config: Res<Config>,
+	mut exit: EventWriter<AppExit>,
+	mut players: Query<(&Player, &Score, &Transform, &RigidBodyVelocity, &mut Track)>,
+	celestial_bodies: Query<(&Transform, &CelestialBody)>,
+) {
+	for (_, _, transform, velocity, mut track) in players.iter_mut() {
+		let near_planet = celestial_bodies.iter().any(|(cb_transform, cb)| {
+			(cb_transform.translation - transform.translation).length() < NEAR_PLANET * cb.radius
+		});
+		track.ticks_near_planets += near_planet as u64;
+		track.speed_sum += velocity.linvel.norm() as f64;
+	}
+
+	simulation.tick += 1;
+	if simulation.tick < simulation.ticks {
+		return;
+	}
+
+	let per_player: Vec<PlayerReport> = players
+		.iter_mut()
+		.map(|(player, score, _, _, track)| PlayerReport {
+			name: player.name.clone(),
+			damage_dealt: score.damage,
+			kills: score.kills,
+			deaths: score.deaths,
+			time_near_planets: track.ticks_near_planets as f64 * TICK_TIME.as_secs_f64(),
+			average_speed: track.speed_sum / simulation.ticks as f64,
+		})
+		.collect();
+	let count = per_player.len().max(1) as f64;
+	let report = Report {
+		players: simulation.players,
+		ticks: simulation.ticks,
+		seed: config.seed.expect("The seed is picked on start."),
+		tuning: config.tuning.clone(),
+		total_damage_dealt: per_player.iter().map(|player| player.damage_dealt).sum(),
+		total_deaths: per_player.iter().map(|player| player.deaths).sum(),
+		average_time_near_planets: per_player
+			.iter()
+			.map(|player| player.time_near_planets)
+			.sum::<f64>()
+			/ count,
+		average_speed: per_player.iter().map(|player| player.average_speed).sum::<f64>() / count,
+		per_player,
+	};
+	println!("{}", serde_json::to_string_pretty(&report).expect("Cannot serialize the report."));
+	exit.send(AppExit);
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::IntoEntity;
+use bevy_rapier2d::rapier::dynamics::RigidBodyMassProps;
+use bevy_rapier2d::rapier::prelude::IntersectionEvent;
+
+use crate::component::{CelestialBody, CelestialForm, Orbit, Player, Shape, ShieldState, HP};
+
+/// Swallow whatever crosses the event horizon of a black hole, adding its mass to the hole.
+/// Players die, shields break, and debris and other celestial bodies vanish.
+pub fn swallow(
+	mut commands: Commands,
+	mut intersections: EventReader<IntersectionEvent>,
+	mut celestial_bodies: Query<(Entity, &mut CelestialBody, &Orbit)>,
+	mut victims: Query<
+		(&mut HP, &RigidBodyMassProps, Option<&Player>, Option<&ShieldState>),
+		Or<(With<Player>, With<ShieldState>, With<Shape>)>,
+	>,
+) {
+	let bodies: Vec<(Entity, CelestialForm, f32, f32, Vec2)> = celestial_bodies
+		.iter_mut()
+		.map(|(entity, cb, orbit)| {
+			let pos = Vec2::new(orbit.pos.x as f32, orbit.pos.y as f32);
+			(entity, cb.form, cb.radius, cb.mass, pos)
+		})
+		.collect();
+	let is_black_hole = |entity: Entity| {
+		bodies.iter().any(|(body, form, ..)| *body == entity && *form == CelestialForm::BlackHole)
+	};
+
+	let mut swallowed = Vec::new();
+	for intersection in intersections.iter() {
+		if !intersection.intersecting {
+			continue;
+		}
+		let (e1, e2) = (intersection.collider1.entity(), intersection.collider2.entity());
+		let (hole, victim) = if is_black_hole(e1) { (e1, e2) } else { (e2, e1) };
+		if !is_black_hole(hole) {
+			continue;
+		}
+		// The victim may have been swallowed earlier in the tick.
+		let (mut hp, mprops, player, shield) = match victims.get_mut(victim) {
+			Ok(victim) => victim,
+			Err(_) => continue,
+		};
+		// Shields belong to their player, whose mass the hole gets if it swallows the player.
+		if shield.is_none() {
+			swallowed.push((hole, mass(mprops)));
+		}
+		if player.is_some() || shield.is_some() {
+			hp.val = 0;
+		} else {
+			commands.entity(victim).despawn();
+		}
+	}
+
+	// Celestial bodies pass through each other in the physics world,
+	// so they are swallowed once their center crosses the event horizon.
+	let mut gone = Vec::new();
+	for &(hole, form, radius, hole_mass, hole_pos) in bodies.iter() {
+		if form != CelestialForm::BlackHole || gone.contains(&hole) {
+			continue;
+		}
+		for &(body, _, _, body_mass, body_pos) in bodies.iter() {
+			// Black holes merge into the heavier one.
+			let lighter = (body_mass, body.to_bits()) < (hole_mass, hole.to_bits());
+			if lighter && !gone.contains(&body) && (body_pos - hole_pos).length() < radius {
+				swallowed.push((hole, body_mass));
+				gone.push(body);
+				commands.entity(body).despawn();
+			}
+		}
+	}
+
+	for (hole, mass) in swallowed {
+		if let Ok((_, mut cb, _)) = celestial_bodies.get_mut(hole) {
+			cb.mass += mass;
+		}
+	}
+}
+
+fn mass(mprops: &RigidBodyMassProps) -> f32 {
+	let inv_mass = mprops.local_mprops.inv_mass;
+	if inv_mass == 0.0 {
+		0.0
+	} else {
+		1.0 / inv_mass
+	}
+}
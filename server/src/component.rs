@@ -1,9 +1,12 @@
 use bevy::ecs::prelude::Entity;
+use bevy_rapier2d::na::Vector2;
+use bevy_rapier2d::prelude::InteractionGroups;
 
-pub enum Shape {
-	Circle,
-}
+pub use game_shared::shape::Shape;
+pub use game_shared::shield::{BashPhase, ShieldType};
+pub use game_shared::CelestialForm;
 
+/// Multiplies the damage the object deals in collisions.
 pub struct Dmg {
 	pub val: i32,
 }
@@ -12,16 +15,45 @@ pub struct HP {
 	pub val: i32,
 }
 
-pub struct ShieldID {
-	pub entity: Entity,
+/// What an object is made of, which decides how it deals and takes damage in collisions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Material {
+	Body,
+	Shield,
+	Debris,
+	Planet,
+	Boundary,
+}
+
+/// The health of a shield beyond its `HP`.
+pub struct ShieldState {
+	/// Whether the shield ran out of HP and lets everything through until it regenerates fully.
+	pub broken: bool,
+	/// Game-clock seconds when the shield or its player last took or dealt damage.
+	pub last_hit: f64,
+	/// HP regenerated but not restored yet, as HP are whole numbers.
+	pub regen: f32,
+	/// The collision groups of the shield while it isn't broken.
+	pub groups: InteractionGroups,
 }
 
-pub enum ShieldType {
-	Circle,
+/// The timed bash of a shield.
+pub struct Bash {
+	pub phase: BashPhase,
+	/// Whether the player holds the bash input.
+	pub requested: bool,
+	/// Game-clock seconds when the last bash started.
+	pub started: f64,
+}
+
+pub struct ShieldID {
+	pub entity: Entity,
 }
 
 pub struct Player {
 	pub name: String,
+	/// The team of the player, if playing in teams.
+	pub team: Option<u32>,
 }
 
 pub struct Thrust {
@@ -29,10 +61,51 @@ pub struct Thrust {
 	pub y: f32,
 }
 
+/// A piece of shattered debris, which disappears at `expires` game-clock seconds.
+pub struct Fragment {
+	pub expires: f64,
+}
+
 pub struct Boundary {
 	pub info: String,
 }
 
 pub struct CelestialBody {
-	pub form: String,
+	pub form: CelestialForm,
+	pub radius: f32,
+	/// The mass pulling other bodies, as the physics world doesn't move celestial bodies.
+	pub mass: f32,
+	/// Whether the body stays in place.
+	pub fixed: bool,
+}
+
+/// The position and velocity of a celestial body, moved by `gravity::orbit` instead of
+/// the physics world.
+pub struct Orbit {
+	pub pos: Vector2<f64>,
+	pub vel: Vector2<f64>,
+}
+
+/// Energy spent on boosts, regenerating over time.
+pub struct Energy {
+	pub val: f32,
+	/// Whether the player holds the boost input.
+	pub boosting: bool,
+	/// Game-clock seconds of the last boost.
+	pub last_boost: f64,
+}
+
+/// Players that recently damaged a player, along with the game-clock time of each hit.
+/// The most recent hit comes last.
+pub struct Attackers {
+	pub hits: Vec<(Entity, f64)>,
+}
+
+pub struct Score {
+	pub kills: u32,
+	pub assists: u32,
+	pub deaths: u32,
+	/// Damage dealt to other players and their shields.
+	pub damage: i32,
+	pub score: i32,
 }
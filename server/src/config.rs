@@ -0,0 +1,193 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::component::Score;
+
+/// Server settings, loaded from a TOML file.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// Length of a round in seconds.
+	pub round_secs: u64,
+	/// Seconds the final standings are shown before the next round starts.
+	pub results_secs: u64,
+	/// File to record a replay of the session to.
+	pub replay_path: Option<String>,
+	/// Also record the whole world every this many ticks, for the client to play back.
+	pub replay_world_ticks: Option<u64>,
+	/// Players in the arena at most, while further players wait in a queue.
+	/// There is no limit if there is none.
+	pub max_players: Option<usize>,
+	/// AI-controlled players join while there are fewer players in the arena.
+	pub min_population: usize,
+	/// Number of teams players are split into, at most `MAX_TEAMS`.
+	/// Everyone plays for themselves if there are none.
+	pub teams: u32,
+	/// Whether teammates damage each other.
+	pub friendly_fire: bool,
+	/// Whether teammates collide with each other.
+	pub friendly_collision: bool,
+	/// Pieces of debris kept on the map, respawning as others shatter.
+	pub debris_count: usize,
+	/// Seconds fragments of shattered debris last.
+	pub fragment_secs: f64,
+	/// Name of a bundled map or path of a map file laying out the arena.
+	pub map: String,
+	/// Name of a bundled scenario or path of a scenario file placing the celestial bodies
+	/// instead of those of the map.
+	pub scenario: Option<String>,
+	/// Seed of the RNG placing debris and players, random if there is none.
+	pub seed: Option<u64>,
+	// Tables have to come after plain values in TOML.
+	pub win_condition: WinCondition,
+	pub tuning: Tuning,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			round_secs: 300,
+			results_secs: 10,
+			replay_path: None,
+			replay_world_ticks: Some(60),
+			max_players: None,
+			min_population: 4,
+			teams: 0,
+			friendly_fire: false,
+			friendly_collision: true,
+			debris_count: 100,
+			fragment_secs: 5.0,
+			map: "arena".to_string(),
+			scenario: None,
+			seed: None,
+			win_condition: WinCondition::TimeLimit,
+			tuning: Tuning::default(),
+		}
+	}
+}
+
+impl Config {
+	/// Load the config from the file at `path` if there is one.
+	pub fn load(path: Option<String>) -> Self {
+		match path {
+			Some(path) => {
+				let content = std::fs::read_to_string(path).expect("Cannot read config file.");
+				toml::from_str(&content).expect("Cannot parse config file.")
+			}
+			None => Config::default(),
+		}
+	}
+
+	pub fn to_toml(&self) -> String {
+		toml::to_string(self).expect("Cannot serialize Config.")
+	}
+
+	pub fn round_duration(&self) -> Duration {
+		Duration::from_secs(self.round_secs)
+	}
+
+	pub fn results_duration(&self) -> Duration {
+		Duration::from_secs(self.results_secs)
+	}
+}
+
+/// Physical parameters of the gameplay.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tuning {
+	pub gravity_const: f32,
+	/// Groups of celestial bodies smaller than this angle, seen from a body, pull it as one.
+	/// 0 computes the exact pull of each of them.
+	pub gravity_opening_angle: f32,
+	pub player_density: f32,
+	pub shield_density: f32,
+	pub debris_density: f32,
+	pub celestial_density: f32,
+	/// Kinetic energy per unit of mass above which colliding planets shatter instead of merging.
+	pub planet_shatter_energy: f32,
+	/// Force applied to players moving.
+	pub thrust: f32,
+	/// Limits of the shield's distance from the body, at rest and pushed.
+	pub shield_limits: [f32; 2],
+	pub shield_push_limits: [f32; 2],
+	pub shield_motor_velocity: f32,
+	/// Limits of the shield's distance from the body and its velocity while bashing.
+	pub shield_bash_limits: [f32; 2],
+	pub shield_bash_velocity: f32,
+	/// Seconds a bash lasts and seconds until the next one.
+	pub bash_secs: f64,
+	pub bash_cooldown: f64,
+	/// Multiplies the damage of a bashing shield.
+	pub bash_dmg_multiplier: f32,
+	/// Impulse pushing away whatever a bashing shield hits.
+	pub bash_knockback: f32,
+	/// Damage of collisions per unit of impulse, before the multipliers of the materials.
+	pub damage_per_impulse: f32,
+	/// Seconds out of combat before shields regenerate.
+	pub shield_regen_delay: f64,
+	/// HP per second shields regenerate.
+	pub shield_regen_rate: f32,
+	/// Impulse of a boost in the direction of movement.
+	pub boost_impulse: f32,
+	/// Energy a boost takes, out of `MAX_ENERGY`.
+	pub boost_cost: f32,
+	/// Seconds between boosts at least.
+	pub boost_cooldown: f64,
+	/// Energy per second players regenerate.
+	pub energy_regen_rate: f32,
+}
+
+impl Default for Tuning {
+	fn default() -> Self {
+		Tuning {
+			gravity_const: 20.0,
+			gravity_opening_angle: 0.5,
+			player_density: 0.0008,
+			shield_density: 0.000008,
+			debris_density: 0.0008,
+			celestial_density: 318.3,
+			planet_shatter_energy: 50000.0,
+			thrust: 20000.0,
+			shield_limits: [-60.0, -55.0],
+			shield_push_limits: [-80.0, -70.0],
+			shield_motor_velocity: -300.0,
+			shield_bash_limits: [-120.0, -110.0],
+			shield_bash_velocity: -1500.0,
+			bash_secs: 0.25,
+			bash_cooldown: 2.0,
+			bash_dmg_multiplier: 2.0,
+			bash_knockback: 600.0,
+			damage_per_impulse: 0.01,
+			shield_regen_delay: 3.0,
+			shield_regen_rate: 20.0,
+			boost_impulse: 800.0,
+			boost_cost: 35.0,
+			boost_cooldown: 0.5,
+			energy_regen_rate: 10.0,
+		}
+	}
+}
+
+/// When a round ends before its time runs out.
+/// The player with the highest score at the end of a round wins.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WinCondition {
+	/// Play until the time runs out.
+	TimeLimit,
+	/// End the round once a player reaches the score.
+	ScoreLimit(i32),
+	/// End the round once a player reaches the number of kills.
+	KillLimit(u32),
+}
+
+impl WinCondition {
+	pub fn is_met(&self, score: &Score) -> bool {
+		match *self {
+			WinCondition::TimeLimit => false,
+			WinCondition::ScoreLimit(limit) => score.score >= limit,
+			WinCondition::KillLimit(limit) => score.kills >= limit,
+		}
+	}
+}
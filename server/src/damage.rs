@@ -0,0 +1,67 @@
+use bevy::math::Vec2;
+
+use crate::component::Material;
+
+/// How a material deals and takes damage in collisions.
+pub struct MaterialProps {
+	/// Multiplies the damage dealt to others.
+	pub dealt: f32,
+	/// Multiplies the damage taken from others.
+	pub taken: f32,
+	/// Impulses below this don't damage the material at all.
+	pub min_impulse: f32,
+}
+
+impl MaterialProps {
+	pub fn of(material: Material) -> Self {
+		let (dealt, taken, min_impulse) = match material {
+			Material::Body => (0.5, 1.0, 200.0),
+			Material::Shield => (1.5, 0.5, 300.0),
+			Material::Debris => (0.5, 1.0, 100.0),
+			Material::Planet => (2.0, 0.5, 1500.0),
+			Material::Boundary => (0.5, 0.0, f32::INFINITY),
+		};
+		MaterialProps { dealt, taken, min_impulse }
+	}
+}
+
+/// One of two objects in a contact.
+#[derive(Clone, Copy, Debug)]
+pub struct Impactor {
+	pub material: Material,
+	/// Zero for objects that don't move, like boundaries.
+	pub inv_mass: f32,
+	pub linvel: Vec2,
+	/// The `Dmg` multiplier of the object.
+	pub dmg: i32,
+}
+
+/// The damage `a` and `b` take from colliding with each other,
+/// proportional to the impulse of the collision.
+pub fn contact_damage(a: &Impactor, b: &Impactor, damage_per_impulse: f32) -> (i32, i32) {
+	let inv_mass = a.inv_mass + b.inv_mass;
+	if inv_mass == 0.0 {
+		return (0, 0);
+	}
+	// The impulse exchanged in an elastic head-on collision at the relative velocity.
+	let impulse = 2.0 * (a.linvel - b.linvel).length() / inv_mass;
+	(damage(impulse, b, a, damage_per_impulse), damage(impulse, a, b, damage_per_impulse))
+}
+
+fn damage(impulse: f32, attacker: &Impactor, victim: &Impactor, damage_per_impulse: f32) -> i32 {
+	let victim_props = MaterialProps::of(victim.material);
+	if impulse < victim_props.min_impulse {
+		return 0;
+	}
+	let dealt = MaterialProps::of(attacker.material).dealt * attacker.dmg as f32;
+	(impulse * damage_per_impulse * dealt * victim_props.taken).round() as i32
+}
+
+/// The inverse mass of two bodies moving as one, e.g. a shield pushed by its player.
+pub fn combined_inv_mass(inv_mass1: f32, inv_mass2: f32) -> f32 {
+	if inv_mass1 == 0.0 || inv_mass2 == 0.0 {
+		0.0
+	} else {
+		inv_mass1 * inv_mass2 / (inv_mass1 + inv_mass2)
+	}
+}
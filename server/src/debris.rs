@@ -0,0 +1,206 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_rapier2d::na::Vector2;
+use bevy_rapier2d::physics::{ColliderBundle, RigidBodyBundle};
+use bevy_rapier2d::prelude::RigidBodyCcd;
+use bevy_rapier2d::rapier::dynamics::RigidBodyVelocity;
+use bevy_rapier2d::rapier::geometry::{ColliderMassProps, ColliderMaterial, ColliderShape};
+use bevy_rapier2d::rapier::math::Point;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use game_shared::shape::Shape;
+use game_shared::{Position, INIT_RADIUS, VIEW_X, VIEW_Y};
+
+use crate::component::{Fragment, Player, HP};
+use crate::config::Config;
+use crate::map::Map;
+use crate::server::{GameClock, GameRng};
+use crate::system::{create_object, INIT_RESTITUTION};
+
+/// Most sides of random polygons.
+const MAX_SIDES: usize = 7;
+/// Pieces debris shatters into.
+const FRAGMENTS: usize = 4;
+/// Speed fragments fly apart with, on top of the motion of the debris.
+const FRAGMENT_SPEED: f32 = 80.0;
+const FRAGMENT_HP: i32 = 20;
+/// Debris respawning per tick at most, so that the map fills up gradually.
+const RESPAWNS_PER_TICK: usize = 1;
+/// Random points tried for a spot out of sight of every player.
+const RESPAWN_TRIES: usize = 10;
+
+/// A random shape of debris, about as large as a player.
+pub fn random_shape(rng: &mut StdRng) -> Shape {
+	match rng.gen_range(0..4) {
+		0 => Shape::Circle { radius: size(rng) },
+		1 => Shape::Rect { half_width: size(rng), half_height: size(rng) },
+		2 => {
+			let radius = size(rng);
+			random_polygon(rng, radius)
+		}
+		_ => {
+			Shape::Capsule { half_length: size(rng), radius: rng.gen_range(0.3..0.8) * INIT_RADIUS }
+		}
+	}
+}
+
+/// A random convex polygon with its vertices at `radius` from the center.
+pub(crate) fn random_polygon(rng: &mut StdRng, radius: f32) -> Shape {
+	let sides = rng.gen_range(3..=MAX_SIDES);
+	// Vertices on a circle are convex, and jittering them apart keeps them from collapsing.
+	let points = (0..sides)
+		.map(|i| {
+			let angle = 2.0 * PI * (i as f32 + rng.gen_range(-0.3..0.3)) / sides as f32;
+			Position { x: radius * angle.cos(), y: radius * angle.sin() }
+		})
+		.collect();
+	Shape::Polygon { points }
+}
+
+/// A random extent of debris.
+fn size(rng: &mut StdRng) -> f32 {
+	rng.gen_range(0.5..1.8) * INIT_RADIUS
+}
+
+/// The collider matching `shape`.
+pub fn collider_shape(shape: &Shape) -> ColliderShape {
+	match shape {
+		Shape::Circle { radius } => ColliderShape::ball(*radius),
+		Shape::Rect { half_width, half_height } => ColliderShape::cuboid(*half_width, *half_height),
+		Shape::Polygon { points } => {
+			let points: Vec<Point<f32>> = points.iter().map(|p| Point::new(p.x, p.y)).collect();
+			ColliderShape::convex_hull(&points).expect("Polygons of debris should be convex.")
+		}
+		Shape::Capsule { half_length, radius } => ColliderShape::capsule(
+			Point::new(-*half_length, 0.0),
+			Point::new(*half_length, 0.0),
+			*radius,
+		),
+	}
+}
+
+/// The distance of the farthest point of `shape` from its center.
+fn bounding_radius(shape: &Shape) -> f32 {
+	match shape {
+		Shape::Circle { radius } => *radius,
+		Shape::Rect { half_width, half_height } => half_width.hypot(*half_height),
+		Shape::Polygon { points } => points.iter().map(|p| p.x.hypot(p.y)).fold(0.0, f32::max),
+		Shape::Capsule { half_length, radius } => half_length + radius,
+	}
+}
+
+/// Spawn debris of `shape` at (`x`, `y`) rotated by `rot` and moving with `velocity`.
+pub fn spawn_debris(
+	commands: &mut Commands,
+	shape: Shape,
+	x: f32,
+	y: f32,
+	rot: f32,
+	velocity: RigidBodyVelocity,
+	config: &Config,
+) -> Entity {
+	let rigid_body = RigidBodyBundle {
+		position: (Vec2::new(x, y), rot).into(),
+		velocity,
+		ccd: RigidBodyCcd { ccd_enabled: true, ..Default::default() },
+		..Default::default()
+	};
+	let collider = ColliderBundle {
+		shape: collider_shape(&shape),
+		mass_properties: ColliderMassProps::Density(config.tuning.debris_density),
+		material: ColliderMaterial { restitution: INIT_RESTITUTION, ..Default::default() },
+		..Default::default()
+	};
+	create_object(commands, shape, x, y, rigid_body, collider)
+}
+
+/// Break debris running out of HP into fragments flying apart, and remove broken fragments.
+pub fn shatter_debris(
+	mut commands: Commands,
+	config: Res<Config>,
+	clock: Res<GameClock>,
+	mut rng: ResMut<GameRng>,
+	debris: Query<(Entity, &HP, &Shape, &Transform, &RigidBodyVelocity, Option<&Fragment>)>,
+) {
+	let expires = clock.now() + config.fragment_secs;
+	for (entity, hp, shape, transform, velocity, fragment) in debris.iter() {
+		if hp.val > 0 {
+			continue;
+		}
+		commands.entity(entity).despawn();
+		// Fragments are too small to shatter any further.
+		if fragment.is_some() {
+			continue;
+		}
+
+		// Lay the fragments out in a ring inside the debris without overlapping each other.
+		let radius = bounding_radius(shape);
+		let start = rng.0.gen_range(-PI..PI);
+		for i in 0..FRAGMENTS {
+			let angle = start + 2.0 * PI * i as f32 / FRAGMENTS as f32;
+			let dir = Vector2::new(angle.cos(), angle.sin());
+			let offset = dir * radius / 2.0;
+			let linvel = velocity.linvel
+				+ Vector2::new(-offset.y, offset.x) * velocity.angvel
+				+ dir * FRAGMENT_SPEED;
+			let velocity = RigidBodyVelocity { linvel, angvel: velocity.angvel };
+			let shape = random_polygon(&mut rng.0, 0.3 * radius);
+			let x = transform.translation.x + offset.x;
+			let y = transform.translation.y + offset.y;
+			let rot = rng.0.gen_range(-PI..PI);
+			let fragment = spawn_debris(&mut commands, shape, x, y, rot, velocity, &config);
+			commands.entity(fragment).insert(Fragment { expires }).insert(HP { val: FRAGMENT_HP });
+		}
+	}
+}
+
+/// Remove fragments that have lasted `Config::fragment_secs`.
+pub fn expire_fragments(
+	mut commands: Commands,
+	clock: Res<GameClock>,
+	fragments: Query<(Entity, &Fragment)>,
+) {
+	let now = clock.now();
+	for (entity, fragment) in fragments.iter() {
+		if fragment.expires <= now {
+			commands.entity(entity).despawn();
+		}
+	}
+}
+
+/// Respawn debris anywhere on the map out of sight of players,
+/// keeping `Config::debris_count` pieces while a round is played.
+pub fn maintain_debris(
+	mut commands: Commands,
+	config: Res<Config>,
+	map: Res<Map>,
+	mut rng: ResMut<GameRng>,
+	debris: Query<Entity, (With<Shape>, Without<Fragment>)>,
+	players: Query<&Transform, With<Player>>,
+) {
+	let missing = config.debris_count.saturating_sub(debris.iter().count());
+	for _ in 0..missing.min(RESPAWNS_PER_TICK) {
+		let (x, y) = match hidden_spot(&map, &mut rng.0, &players) {
+			Some(spot) => spot,
+			None => return,
+		};
+		let shape = random_shape(&mut rng.0);
+		let rot = rng.0.gen_range(-PI..PI);
+		spawn_debris(&mut commands, shape, x, y, rot, RigidBodyVelocity::default(), &config);
+	}
+}
+
+/// A random point of the debris fields of `map` out of the view of every player, if one is found.
+fn hidden_spot(
+	map: &Map,
+	rng: &mut StdRng,
+	players: &Query<&Transform, With<Player>>,
+) -> Option<(f32, f32)> {
+	(0..RESPAWN_TRIES).map(|_| map.debris_point(rng)).find(|(x, y)| {
+		players.iter().all(|pos| {
+			(pos.translation.x - x).abs() >= VIEW_X || (pos.translation.y - y).abs() >= VIEW_Y
+		})
+	})
+}
@@ -3,7 +3,7 @@ use bevy::ecs::entity::Entity;
 use futures::channel::mpsc::UnboundedReceiver;
 use futures::channel::oneshot::Sender;
 
-use game_shared::PlayerState;
+use game_shared::{JoinInfo, PlayerState};
 
 use crate::WsSession;
 
@@ -31,7 +31,7 @@ impl Iterator for Drain<'_> {
 }
 
 pub enum GameEvent {
-	CreatePlayer(String, Sender<Entity>, Addr<WsSession>),
+	CreatePlayer(JoinInfo, Sender<Entity>, Addr<WsSession>),
 	RemovePlayer(Entity),
 	UpdatePlayer(Entity, PlayerState),
 }
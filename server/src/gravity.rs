@@ -0,0 +1,143 @@
+use bevy::math::Vec2;
+use bevy::prelude::*;
+use bevy_rapier2d::na::Vector2;
+use bevy_rapier2d::rapier::dynamics::{
+	IntegrationParameters, RigidBodyPosition, RigidBodyVelocity,
+};
+use bevy_rapier2d::rapier::math::Isometry;
+
+use crate::component::{CelestialBody, Orbit};
+use crate::config::Config;
+
+/// Leapfrog steps celestial bodies take per step of the physics world.
+const ORBIT_SUBSTEPS: usize = 4;
+
+/// A point mass pulling other bodies towards it.
+#[derive(Clone, Copy, Debug)]
+pub struct Attractor {
+	pub pos: Vec2,
+	pub mass: f32,
+}
+
+/// The gravitational acceleration of a body at `pos` towards `attractors`.
+/// Attractors right at `pos`, like the body itself, don't pull it.
+pub fn acceleration(pos: Vec2, attractors: &[Attractor], gravity_const: f32) -> Vec2 {
+	attractors.iter().fold(Vec2::ZERO, |accel, attractor| {
+		let disp = attractor.pos - pos;
+		let dist = disp.length();
+		if dist == 0.0 {
+			accel
+		} else {
+			accel + gravity_const * attractor.mass * disp / dist.powi(3)
+		}
+	})
+}
+
+/// A celestial body moving under the gravity of the others.
+/// Kept in double precision so that orbits don't drift apart over hours.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orbiter {
+	pub pos: Vector2<f64>,
+	pub vel: Vector2<f64>,
+	pub mass: f64,
+	pub radius: f64,
+	/// Whether the body pulls the others without moving itself.
+	pub fixed: bool,
+}
+
+/// The distance between `a` and `b` gravity acts over.
+/// Overlapping bodies are pulled as if they were touching, so that the pull stays finite.
+fn gravity_dist(a: &Orbiter, b: &Orbiter) -> f64 {
+	(b.pos - a.pos).norm().max(a.radius + b.radius)
+}
+
+/// The accelerations of `bodies` from their mutual gravity.
+pub fn accelerations(bodies: &[Orbiter], gravity_const: f64) -> Vec<Vector2<f64>> {
+	let mut accels = vec![Vector2::zeros(); bodies.len()];
+	for i in 0..bodies.len() {
+		for j in i + 1..bodies.len() {
+			let disp = bodies[j].pos - bodies[i].pos;
+			let dist = disp.norm();
+			if dist == 0.0 {
+				continue;
+			}
+			let pull = gravity_const * disp / (dist * gravity_dist(&bodies[i], &bodies[j]).powi(2));
+			accels[i] += pull * bodies[j].mass;
+			accels[j] -= pull * bodies[i].mass;
+		}
+	}
+	accels
+}
+
+/// Advance `bodies` by `dt` with a kick-drift-kick leapfrog step.
+/// Being symplectic, it keeps the energy of orbits from drifting over long runs.
+pub fn leapfrog(bodies: &mut [Orbiter], gravity_const: f64, dt: f64) {
+	kick(bodies, &accelerations(bodies, gravity_const), dt / 2.0);
+	for body in bodies.iter_mut().filter(|body| !body.fixed) {
+		body.pos += body.vel * dt;
+	}
+	kick(bodies, &accelerations(bodies, gravity_const), dt / 2.0);
+}
+
+fn kick(bodies: &mut [Orbiter], accels: &[Vector2<f64>], dt: f64) {
+	for (body, accel) in bodies.iter_mut().zip(accels) {
+		if !body.fixed {
+			body.vel += accel * dt;
+		}
+	}
+}
+
+/// The kinetic and potential energy of `bodies` apart from each other, which gravity conserves.
+pub fn energy(bodies: &[Orbiter], gravity_const: f64) -> f64 {
+	let kinetic: f64 = bodies.iter().map(|body| body.mass * body.vel.norm_squared() / 2.0).sum();
+	let mut potential = 0.0;
+	for i in 0..bodies.len() {
+		for j in i + 1..bodies.len() {
+			let dist = (bodies[j].pos - bodies[i].pos).norm();
+			potential -= gravity_const * bodies[i].mass * bodies[j].mass / dist;
+		}
+	}
+	kinetic + potential
+}
+
+/// Move celestial bodies under their mutual gravity, in substeps of a step of the physics world
+/// so that they keep up with the bodies it moves.
+/// Their velocity is kept up to date for the damage of impacts with them.
+pub fn orbit(
+	config: Res<Config>,
+	integration: Res<IntegrationParameters>,
+	mut bodies: Query<(
+		Entity,
+		&CelestialBody,
+		&mut Orbit,
+		&mut RigidBodyPosition,
+		&mut RigidBodyVelocity,
+	)>,
+) {
+	let (entities, mut orbiters): (Vec<Entity>, Vec<Orbiter>) = bodies
+		.iter_mut()
+		.map(|(entity, cb, orbit, ..)| {
+			let orbiter = Orbiter {
+				pos: orbit.pos,
+				vel: orbit.vel,
+				mass: cb.mass as f64,
+				radius: cb.radius as f64,
+				fixed: cb.fixed,
+			};
+			(entity, orbiter)
+		})
+		.unzip();
+	let gravity_const = config.tuning.gravity_const as f64;
+	let dt = integration.dt as f64 / ORBIT_SUBSTEPS as f64;
+	for _ in 0..ORBIT_SUBSTEPS {
+		leapfrog(&mut orbiters, gravity_const, dt);
+	}
+	for (entity, orbiter) in entities.into_iter().zip(orbiters) {
+		let (_, _, mut orbit, mut position, mut velocity) =
+			bodies.get_mut(entity).expect("No component found.");
+		orbit.pos = orbiter.pos;
+		orbit.vel = orbiter.vel;
+		position.next_position = Isometry::translation(orbiter.pos.x as f32, orbiter.pos.y as f32);
+		velocity.linvel = Vector2::new(orbiter.vel.x as f32, orbiter.vel.y as f32);
+	}
+}
@@ -0,0 +1,353 @@
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use actix::prelude::*;
+use actix_files as fs;
+use actix_web::dev::Server;
+use actix_web::error::ErrorNotFound;
+use actix_web::{web, App, Error, HttpRequest, HttpResponse, HttpServer};
+use actix_web_actors::ws;
+use bevy::app::ScheduleRunnerSettings;
+use bevy::core::FixedTimestep;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::schedule::{
+	ExclusiveSystemDescriptorCoercion, ParallelSystemDescriptorCoercion, StageLabel, SystemLabel,
+	SystemSet, SystemStage,
+};
+use bevy::ecs::system::{IntoExclusiveSystem, IntoSystem};
+use bevy::prelude::{AppBuilder, CoreStage};
+use bevy::MinimalPlugins;
+use bevy_rapier2d::physics::{RapierConfiguration, RapierPhysicsPlugin, TimestepMode};
+use bevy_rapier2d::prelude::NoUserData;
+use bevy_rapier2d::rapier::dynamics::IntegrationParameters;
+
+use game_shared::replay::ReplayHeader;
+use game_shared::{Operation, ServerMessage};
+
+use crate::config::Config;
+use crate::event::EventListener;
+use crate::map::Map;
+use crate::replay::Recorder;
+use crate::server::{GameClock, GameProxy, GameRng, GameServer};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+pub const TICK_TIME: Duration = Duration::from_millis(16);
+pub const LEADERBOARD_INTERVAL: Duration = Duration::from_secs(1);
+
+pub mod ai;
+pub mod barnes_hut;
+pub mod black_hole;
+pub mod component;
+pub mod config;
+pub mod damage;
+pub mod debris;
+pub mod event;
+pub mod gravity;
+pub mod map;
+pub mod planet;
+pub mod replay;
+pub mod scenario;
+pub mod server;
+pub mod shield;
+pub mod system;
+
+pub struct WsSession {
+	hb: Instant,
+	player_entity: Option<Entity>,
+	proxy: GameProxy,
+}
+
+impl Actor for WsSession {
+	type Context = ws::WebsocketContext<Self>;
+
+	fn started(&mut self, ctx: &mut Self::Context) {
+		self.hb(ctx);
+	}
+
+	fn stopped(&mut self, _ctx: &mut Self::Context) {
+		// Also when the connection drops without a close frame, e.g. when it is reset.
+		self.proxy.remove_player(self.player_entity.take());
+	}
+}
+
+struct Push(ServerMessage);
+
+impl Message for Push {
+	type Result = ();
+}
+
+impl Handler<Push> for WsSession {
+	type Result = ();
+
+	fn handle(&mut self, msg: Push, ctx: &mut Self::Context) -> Self::Result {
+		ctx.binary(msg.0.serialize());
+	}
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsSession {
+	fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+		match msg {
+			Ok(ws::Message::Pong(_)) => {
+				self.hb = Instant::now();
+			}
+			Ok(ws::Message::Binary(bin)) => {
+				match Operation::deserialize(bin.as_ref()) {
+					Operation::Join(info) => {
+						let (sender, receiver) = futures::channel::oneshot::channel();
+						self.proxy.create_player(info, sender, ctx.address());
+						// Not blocking the session, which may wait in the queue for a long time.
+						receiver
+							.into_actor(self)
+							.then(|e, act, _ctx| {
+								act.player_entity = e.ok();
+								fut::ready(())
+							})
+							.spawn(ctx);
+					}
+					Operation::Update(player_state) => {
+						self.proxy.change_movement(self.player_entity, player_state)
+					}
+					// Unused
+					Operation::Leave => self.proxy.remove_player(self.player_entity),
+				}
+			}
+			Ok(ws::Message::Close(reason)) => {
+				ctx.close(reason);
+				ctx.stop();
+			}
+			_ => (),
+		}
+	}
+}
+
+impl WsSession {
+	fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
+		ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
+			if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
+				println!("Heartbeat failed!");
+				ctx.stop();
+				return;
+			}
+			ctx.ping(b"");
+		});
+	}
+}
+
+pub async fn index(
+	req: HttpRequest,
+	stream: web::Payload,
+	data: web::Data<GameProxy>,
+) -> Result<HttpResponse, Error> {
+	let res = ws::start(
+		WsSession { hb: Instant::now(), player_entity: None, proxy: data.as_ref().clone() },
+		&req,
+		stream,
+	);
+	println!("{:?}", res);
+	res
+}
+
+/// Path of the replay being recorded.
+pub struct ReplayPath(pub Option<String>);
+
+/// Serve the replay being recorded so far.
+pub async fn replay(path: web::Data<ReplayPath>) -> Result<fs::NamedFile, Error> {
+	match &path.0 {
+		Some(path) => Ok(fs::NamedFile::open(path)?),
+		None => Err(ErrorNotFound("No replay is being recorded.")),
+	}
+}
+
+/// A round is `Playing` until it ends and the world freezes in `GameOver`.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum GameState {
+	Playing,
+	GameOver,
+}
+
+/// The stage the game ticks in, before the physics world is stepped.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, StageLabel)]
+pub struct TickStage;
+
+/// Labels of the systems of a tick, which run in this order, each after the one before,
+/// so that the same seed and inputs yield the same world.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Hash, SystemLabel)]
+pub enum Tick {
+	Events,
+	Population,
+	Bots,
+	RotateShields,
+	PushShields,
+	Boost,
+	Orbits,
+	Forces,
+	Damage,
+	Swallow,
+	CollidePlanets,
+	ShatterPlanets,
+	Deaths,
+	ShatterDebris,
+	ExpireFragments,
+	MaintainDebris,
+	RestoreHp,
+	RoundEnd,
+	Render,
+	Record,
+}
+
+/// Add the game world and its systems to `app`.
+/// The game ticks on every update of `app`, stepping the physics world by `TICK_TIME`.
+/// The world is seeded with `config.seed`, or a random seed if there is none.
+pub fn build_game(app: &mut AppBuilder, config: Config, events: EventListener) {
+	assert!(config.teams <= system::MAX_TEAMS, "There can be at most {} teams.", system::MAX_TEAMS);
+	let seed = config.seed.unwrap_or_else(rand::random);
+	println!("The world is seeded with {}.", seed);
+	app.add_stage_before(CoreStage::Update, TickStage, SystemStage::parallel())
+		.add_state_to_stage(TickStage, GameState::Playing)
+		.add_system_set_to_stage(
+			TickStage,
+			SystemSet::on_enter(GameState::Playing).with_system(system::setup.system()),
+		)
+		.add_system_set_to_stage(
+			TickStage,
+			SystemSet::on_exit(GameState::GameOver).with_system(system::reset_world.system()),
+		)
+		.add_system_set_to_stage(
+			TickStage,
+			SystemSet::on_update(GameState::GameOver)
+				.with_system(system::check_round_start.system()),
+		)
+		// Everything in the world moves on the same clock, the physics world included.
+		.insert_resource(RapierConfiguration {
+			timestep_mode: TimestepMode::FixedTimestep,
+			..Default::default()
+		})
+		.insert_resource(IntegrationParameters {
+			dt: TICK_TIME.as_secs_f32(),
+			..Default::default()
+		})
+		.insert_resource(Map::load(&config.map))
+		.insert_resource(config)
+		.insert_resource(GameServer::new())
+		.insert_resource(GameRng::new(seed))
+		.insert_resource(GameClock::default())
+		.insert_resource(events)
+		// Players can still join in `GameOver` and receive snapshots of the frozen world,
+		// and the clock keeps going for the results to be shown.
+		.add_system_set_to_stage(
+			TickStage,
+			SystemSet::new()
+				.with_system(system::advance_clock.exclusive_system().at_start())
+				.with_system(system::handle_events.system().label(Tick::Events))
+				.with_system(
+					system::extract_render_state.system().label(Tick::Render).after(Tick::RoundEnd),
+				)
+				.with_system(system::record_world.system().label(Tick::Record).after(Tick::Render)),
+		)
+		.add_system_set_to_stage(
+			TickStage,
+			SystemSet::on_update(GameState::Playing)
+				.with_system(
+					ai::maintain_population.system().label(Tick::Population).after(Tick::Events),
+				)
+				.with_system(ai::drive_bots.system().label(Tick::Bots).after(Tick::Population))
+				.with_system(
+					system::rotate_shield.system().label(Tick::RotateShields).after(Tick::Bots),
+				)
+				.with_system(
+					system::push_shield
+						.system()
+						.label(Tick::PushShields)
+						.after(Tick::RotateShields),
+				)
+				.with_system(system::boost.system().label(Tick::Boost).after(Tick::PushShields))
+				.with_system(gravity::orbit.system().label(Tick::Orbits).after(Tick::Boost))
+				.with_system(system::simulate.system().label(Tick::Forces).after(Tick::Orbits))
+				.with_system(system::compute_dmg.system().label(Tick::Damage).after(Tick::Forces))
+				.with_system(black_hole::swallow.system().label(Tick::Swallow).after(Tick::Damage))
+				.with_system(
+					planet::collide_planets
+						.system()
+						.label(Tick::CollidePlanets)
+						.after(Tick::Swallow),
+				)
+				.with_system(
+					planet::shatter_planets
+						.system()
+						.label(Tick::ShatterPlanets)
+						.after(Tick::CollidePlanets),
+				)
+				.with_system(
+					system::handle_deaths.system().label(Tick::Deaths).after(Tick::ShatterPlanets),
+				)
+				.with_system(
+					debris::shatter_debris.system().label(Tick::ShatterDebris).after(Tick::Deaths),
+				)
+				.with_system(
+					debris::expire_fragments
+						.system()
+						.label(Tick::ExpireFragments)
+						.after(Tick::ShatterDebris),
+				)
+				.with_system(
+					debris::maintain_debris
+						.system()
+						.label(Tick::MaintainDebris)
+						.after(Tick::ExpireFragments),
+				)
+				.with_system(
+					system::restore_hp.system().label(Tick::RestoreHp).after(Tick::MaintainDebris),
+				)
+				.with_system(
+					system::check_round_end.system().label(Tick::RoundEnd).after(Tick::RestoreHp),
+				),
+		)
+		.add_system_set(
+			SystemSet::new()
+				.with_run_criteria(FixedTimestep::step(LEADERBOARD_INTERVAL.as_secs_f64()))
+				.with_system(system::broadcast_leaderboard.system()),
+		);
+}
+
+/// Run the game on its own thread and serve it on `addr`, e.g. `127.0.0.1:0` for any free port.
+/// Returns the running server and the address it is bound to.
+///
+/// Has to be called inside an actix system. The game keeps running until the process exits.
+pub fn start_server(mut config: Config, addr: &str) -> std::io::Result<(Server, SocketAddr)> {
+	let (s1, r1) = futures::channel::mpsc::unbounded();
+	// Pick the seed here so that the replay can reproduce the world.
+	let seed = *config.seed.get_or_insert_with(rand::random);
+	let recorder = config.replay_path.as_ref().map(|path| {
+		let header =
+			ReplayHeader { seed: Some(seed), config: config.to_toml(), tick_time: TICK_TIME };
+		let map = Map::load(&config.map).view();
+		Recorder::create(path, &header, &map, config.replay_world_ticks)
+	});
+	let replay_path = config.replay_path.clone();
+
+	let server = HttpServer::new(move || {
+		App::new()
+			.data(GameProxy::new(s1.clone()))
+			.data(ReplayPath(replay_path.clone()))
+			.service(web::resource("/ws").route(web::get().to(index)))
+			.service(web::resource("/replay").route(web::get().to(replay)))
+			.service(fs::Files::new("/", "dist/").index_file("index.html"))
+	})
+	.bind(addr)?;
+	let addr = server.addrs()[0];
+
+	std::thread::spawn(move || {
+		let mut app = bevy::app::App::build();
+		if let Some(recorder) = recorder {
+			app.insert_resource(recorder);
+		}
+		// Tick in real time.
+		app.insert_resource(ScheduleRunnerSettings::run_loop(TICK_TIME))
+			.add_plugins(MinimalPlugins)
+			.add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+		build_game(&mut app, config, EventListener(r1));
+		app.run();
+	});
+	Ok((server.run(), addr))
+}
@@ -0,0 +1,217 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use game_shared::shape::Shape;
+use game_shared::{MapView, ObstacleView, Position, MAP_HEIGHT, MAP_WIDTH};
+
+use crate::scenario::{CelestialSpec, Scenario};
+
+/// Maps shipped with the server, by name.
+const BUNDLED: [(&str, &str); 2] = [
+	("arena", include_str!("../maps/arena.toml")),
+	("asteroids", include_str!("../maps/asteroids.toml")),
+];
+
+/// The layout of an arena, loaded from a TOML file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Map {
+	#[serde(default = "default_width")]
+	pub width: f32,
+	#[serde(default = "default_height")]
+	pub height: f32,
+	/// Walls as polylines, each point joined to the next.
+	/// Repeating the first point at the end closes a polyline.
+	#[serde(default)]
+	pub boundaries: Vec<Polyline>,
+	#[serde(default)]
+	pub obstacles: Vec<Obstacle>,
+	/// Regions players spawn in, the middle of the map if there are none.
+	#[serde(default)]
+	pub spawns: Vec<Region>,
+	/// Regions debris appears in, the whole map if there are none.
+	#[serde(default)]
+	pub debris_fields: Vec<Region>,
+	/// Name of a bundled scenario or path of a scenario file adding celestial bodies.
+	pub scenario: Option<String>,
+	/// Celestial bodies besides those of the scenario.
+	#[serde(default)]
+	pub bodies: Vec<CelestialSpec>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Polyline {
+	pub points: Vec<[f32; 2]>,
+}
+
+/// A static object players and debris bounce off.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Obstacle {
+	pub shape: Shape,
+	pub x: f32,
+	pub y: f32,
+	#[serde(default)]
+	pub rot: f32,
+}
+
+/// A rectangle with its lower corner at (`x`, `y`).
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Region {
+	pub x: f32,
+	pub y: f32,
+	pub width: f32,
+	pub height: f32,
+}
+
+fn default_width() -> f32 {
+	MAP_WIDTH
+}
+
+fn default_height() -> f32 {
+	MAP_HEIGHT
+}
+
+impl Map {
+	/// The bundled map called `name`, or else the map in the file at `name`.
+	///
+	/// Panics if an obstacle has no area for anything to bounce off, naming the obstacle.
+	pub fn load(name: &str) -> Self {
+		let content = match BUNDLED.iter().find(|(bundled, _)| *bundled == name) {
+			Some((_, content)) => content.to_string(),
+			None => std::fs::read_to_string(name).expect("Cannot read map file."),
+		};
+		let map: Map = toml::from_str(&content).expect("Cannot parse map file.");
+		for (i, obstacle) in map.obstacles.iter().enumerate() {
+			if let Some(problem) = shape_problem(&obstacle.shape) {
+				panic!("Obstacle {} of map {} should be {}.", i, name, problem);
+			}
+		}
+		map
+	}
+
+	/// Names of the bundled maps.
+	pub fn bundled() -> impl Iterator<Item = &'static str> {
+		BUNDLED.iter().map(|(name, _)| *name)
+	}
+
+	/// The celestial bodies of the map, with those of `scenario` instead of its own if given.
+	pub fn celestial_bodies(&self, scenario: Option<&str>) -> Vec<CelestialSpec> {
+		match scenario {
+			Some(scenario) => Scenario::load(scenario).bodies,
+			None => {
+				let scenario = self.scenario.as_deref().map(Scenario::load);
+				let bodies = scenario.map(|scenario| scenario.bodies).unwrap_or_default();
+				bodies.into_iter().chain(self.bodies.iter().cloned()).collect()
+			}
+		}
+	}
+
+	/// A random point to spawn players at.
+	pub fn spawn_point(&self, rng: &mut StdRng) -> (f32, f32) {
+		let middle = Region {
+			x: 0.4 * self.width,
+			y: 0.4 * self.height,
+			width: 0.2 * self.width,
+			height: 0.2 * self.height,
+		};
+		Region::pick(&self.spawns, rng).unwrap_or(middle).sample(rng)
+	}
+
+	/// A random point to place debris at.
+	pub fn debris_point(&self, rng: &mut StdRng) -> (f32, f32) {
+		let whole = Region { x: 0.0, y: 0.0, width: self.width, height: self.height };
+		Region::pick(&self.debris_fields, rng).unwrap_or(whole).sample(rng)
+	}
+
+	/// The geometry clients render.
+	pub fn view(&self) -> MapView {
+		MapView {
+			width: self.width,
+			height: self.height,
+			boundaries: self
+				.boundaries
+				.iter()
+				.map(|boundary| boundary.points.iter().map(|&[x, y]| Position { x, y }).collect())
+				.collect(),
+			obstacles: self
+				.obstacles
+				.iter()
+				.map(|obstacle| ObstacleView {
+					pos: Position { x: obstacle.x, y: obstacle.y },
+					rot: obstacle.rot,
+					shape: obstacle.shape.clone(),
+				})
+				.collect(),
+		}
+	}
+}
+
+/// What `shape` should be to have an area, if it has none.
+fn shape_problem(shape: &Shape) -> Option<&'static str> {
+	// Both are false for NaN.
+	let positive = |size: f32| size > 0.0 && size.is_finite();
+	let non_negative = |size: f32| size >= 0.0 && size.is_finite();
+	let (valid, expected) = match shape {
+		Shape::Circle { radius } => (positive(*radius), "a circle of positive radius"),
+		Shape::Rect { half_width, half_height } => (
+			positive(*half_width) && positive(*half_height),
+			"a rect of positive half width and half height",
+		),
+		Shape::Polygon { points } => {
+			(spans_area(points), "a polygon of at least 3 points not on a line")
+		}
+		Shape::Capsule { half_length, radius } => (
+			non_negative(*half_length) && positive(*radius),
+			"a capsule of positive radius and no negative half length",
+		),
+	};
+	if valid {
+		None
+	} else {
+		Some(expected)
+	}
+}
+
+/// Whether `points` are not all on a line, so that their convex hull has an area.
+fn spans_area(points: &[Position]) -> bool {
+	let first = match points.first() {
+		Some(first) => first,
+		None => return false,
+	};
+	points.iter().any(|a| {
+		points.iter().any(|b| {
+			let cross = (a.x - first.x) * (b.y - first.y) - (a.y - first.y) * (b.x - first.x);
+			cross.abs() > f32::EPSILON
+		})
+	})
+}
+
+impl Region {
+	/// One of `regions` picked by area, so that points are spread evenly over all of them.
+	pub fn pick(regions: &[Region], rng: &mut StdRng) -> Option<Region> {
+		let total: f32 = regions.iter().map(Region::area).sum();
+		let mut left = rng.gen_range(0.0..=total);
+		for region in regions {
+			left -= region.area();
+			if left <= 0.0 {
+				return Some(*region);
+			}
+		}
+		regions.last().copied()
+	}
+
+	pub fn area(&self) -> f32 {
+		self.width * self.height
+	}
+
+	/// A random point in the region.
+	pub fn sample(&self, rng: &mut StdRng) -> (f32, f32) {
+		let x = self.x + rng.gen::<f32>() * self.width;
+		let y = self.y + rng.gen::<f32>() * self.height;
+		(x, y)
+	}
+
+	pub fn contains(&self, x: f32, y: f32) -> bool {
+		x >= self.x && x <= self.x + self.width && y >= self.y && y <= self.y + self.height
+	}
+}
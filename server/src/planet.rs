@@ -0,0 +1,151 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_rapier2d::na::Vector2;
+use bevy_rapier2d::rapier::dynamics::RigidBodyVelocity;
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::component::{CelestialBody, CelestialForm, Orbit, HP};
+use crate::config::Config;
+use crate::debris::{random_polygon, spawn_debris};
+use crate::scenario::CelestialSpec;
+use crate::server::GameRng;
+use crate::system::create_planet;
+
+/// Pieces planets shatter into.
+const SHARDS: usize = 8;
+/// Speed shards fly apart with, on top of the motion of the planet.
+const SHARD_SPEED: f32 = 150.0;
+
+/// Two celestial bodies running into each other, as they are before the impact.
+#[derive(Clone, Copy)]
+pub struct Impact<'a> {
+	pub bodies: [(&'a CelestialBody, &'a Orbit); 2],
+}
+
+impl Impact<'_> {
+	/// The kinetic energy of the impact per unit of mass of both bodies,
+	/// in the frame of their center of mass.
+	pub fn energy(&self) -> f64 {
+		let [(cb1, orbit1), (cb2, orbit2)] = self.bodies;
+		let (m1, m2) = (cb1.mass as f64, cb2.mass as f64);
+		let reduced_mass = m1 * m2 / (m1 + m2);
+		reduced_mass * (orbit1.vel - orbit2.vel).norm_squared() / 2.0 / (m1 + m2)
+	}
+
+	/// The body both merge into, conserving their mass, momentum and area.
+	/// A fixed body stays in place with whatever hits it.
+	pub fn merged(&self) -> CelestialSpec {
+		let [(cb1, orbit1), (cb2, orbit2)] = self.bodies;
+		let (m1, m2) = (cb1.mass as f64, cb2.mass as f64);
+		let mass = m1 + m2;
+		let (pos, vel) = match self.bodies.iter().find(|(cb, _)| cb.fixed) {
+			Some((_, orbit)) => (orbit.pos, Vector2::zeros()),
+			None => (
+				(orbit1.pos * m1 + orbit2.pos * m2) / mass,
+				(orbit1.vel * m1 + orbit2.vel * m2) / mass,
+			),
+		};
+		CelestialSpec {
+			form: if cb1.mass >= cb2.mass { cb1.form } else { cb2.form },
+			x: pos.x as f32,
+			y: pos.y as f32,
+			radius: cb1.radius.hypot(cb2.radius),
+			mass: Some(mass as f32),
+			velocity: [vel.x as f32, vel.y as f32],
+			fixed: cb1.fixed || cb2.fixed,
+		}
+	}
+
+	/// Whether the bodies shatter instead of merging, which fixed bodies never do.
+	pub fn shatters(&self, shatter_energy: f32) -> bool {
+		self.bodies.iter().all(|(cb, _)| !cb.fixed) && self.energy() > shatter_energy as f64
+	}
+}
+
+/// Merge celestial bodies running into each other, or shatter them into debris
+/// if the impact is more energetic than `Tuning::planet_shatter_energy`.
+pub fn collide_planets(
+	mut commands: Commands,
+	config: Res<Config>,
+	mut rng: ResMut<GameRng>,
+	bodies: Query<(Entity, &CelestialBody, &Orbit)>,
+) {
+	let bodies: Vec<(Entity, &CelestialBody, &Orbit)> = bodies.iter().collect();
+	// Black holes swallow whatever crosses their event horizon instead.
+	let swallowed = |orbit: &Orbit| {
+		bodies.iter().any(|(_, cb, hole)| {
+			cb.form == CelestialForm::BlackHole && (hole.pos - orbit.pos).norm() < cb.radius as f64
+		})
+	};
+	let planets: Vec<(Entity, &CelestialBody, &Orbit)> = bodies
+		.iter()
+		.copied()
+		.filter(|(_, cb, orbit)| cb.form != CelestialForm::BlackHole && !swallowed(orbit))
+		.collect();
+
+	// Celestial bodies pass through each other in the physics world,
+	// so they collide once they overlap.
+	let mut gone = Vec::new();
+	for (i, &(e1, cb1, orbit1)) in planets.iter().enumerate() {
+		for &(e2, cb2, orbit2) in planets[i + 1..].iter() {
+			let dist = (orbit2.pos - orbit1.pos).norm() as f32;
+			if dist >= cb1.radius + cb2.radius || gone.contains(&e1) || gone.contains(&e2) {
+				continue;
+			}
+			gone.extend([e1, e2].iter().copied());
+			commands.entity(e1).despawn();
+			commands.entity(e2).despawn();
+
+			let impact = Impact { bodies: [(cb1, orbit1), (cb2, orbit2)] };
+			if impact.shatters(config.tuning.planet_shatter_energy) {
+				for (cb, orbit) in impact.bodies.iter() {
+					shatter(&mut commands, &mut rng.0, cb, orbit, &config);
+				}
+			} else {
+				create_planet(&mut commands, &impact.merged(), config.tuning.celestial_density);
+			}
+		}
+	}
+}
+
+/// Shatter planets worn down to no HP by impacts into debris.
+pub fn shatter_planets(
+	mut commands: Commands,
+	config: Res<Config>,
+	mut rng: ResMut<GameRng>,
+	planets: Query<(Entity, &HP, &CelestialBody, &Orbit)>,
+) {
+	for (entity, hp, cb, orbit) in planets.iter() {
+		if hp.val > 0 || cb.form == CelestialForm::BlackHole {
+			continue;
+		}
+		commands.entity(entity).despawn();
+		shatter(&mut commands, &mut rng.0, cb, orbit, &config);
+	}
+}
+
+/// Break a celestial body into shards flying apart, laid out in a ring inside it.
+fn shatter(
+	commands: &mut Commands,
+	rng: &mut StdRng,
+	cb: &CelestialBody,
+	orbit: &Orbit,
+	config: &Config,
+) {
+	let start = rng.gen_range(-PI..PI);
+	for i in 0..SHARDS {
+		let angle = start + 2.0 * PI * i as f32 / SHARDS as f32;
+		let dir = Vector2::new(angle.cos(), angle.sin());
+		// Far enough out for neighbouring shards not to overlap.
+		let offset = dir * cb.radius * 0.8;
+		let linvel = Vector2::new(orbit.vel.x as f32, orbit.vel.y as f32) + dir * SHARD_SPEED;
+		let velocity = RigidBodyVelocity { linvel, angvel: 0.0 };
+		let shape = random_polygon(rng, 0.3 * cb.radius);
+		let x = orbit.pos.x as f32 + offset.x;
+		let y = orbit.pos.y as f32 + offset.y;
+		let rot = rng.gen_range(-PI..PI);
+		spawn_debris(commands, shape, x, y, rot, velocity, config);
+	}
+}
@@ -0,0 +1,53 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use game_shared::replay::{RecordedEvent, ReplayHeader, ReplayRecord};
+use game_shared::{MapView, ViewSnapshot};
+
+/// Records a session to a replay file as it is played.
+pub struct Recorder {
+	writer: BufWriter<File>,
+	/// The current tick, starting from 1.
+	tick: u64,
+	/// Record the whole world every this many ticks if set.
+	world_ticks: Option<u64>,
+}
+
+impl Recorder {
+	/// Start recording to the file at `path`, beginning with `header` and the `map` played on.
+	pub fn create(
+		path: &str,
+		header: &ReplayHeader,
+		map: &MapView,
+		world_ticks: Option<u64>,
+	) -> Self {
+		let file = File::create(path).expect("Cannot create replay file.");
+		let mut writer = BufWriter::new(file);
+		header.write_to(&mut writer);
+		ReplayRecord::Map(0, map.clone()).write_to(&mut writer);
+		println!("Recording replay to {}.", path);
+		Recorder { writer, tick: 0, world_ticks }
+	}
+
+	/// Move on to the next tick and record its inbound `events`.
+	pub fn record_tick(&mut self, events: Vec<RecordedEvent>) {
+		self.tick += 1;
+		if !events.is_empty() {
+			ReplayRecord::Events(self.tick, events).write_to(&mut self.writer);
+			self.writer.flush().expect("Cannot write replay file.");
+		}
+	}
+
+	/// If the whole world should be recorded at the current tick.
+	pub fn wants_world(&self) -> bool {
+		match self.world_ticks {
+			Some(ticks) => ticks > 0 && self.tick % ticks == 0,
+			None => false,
+		}
+	}
+
+	pub fn record_world(&mut self, view: ViewSnapshot) {
+		ReplayRecord::World(self.tick, view).write_to(&mut self.writer);
+		self.writer.flush().expect("Cannot write replay file.");
+	}
+}
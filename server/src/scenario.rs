@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use game_shared::{CelestialForm, CELESTIAL_RADIUS};
+
+/// Scenarios shipped with the server, by name.
+const BUNDLED: [(&str, &str); 4] = [
+	("figure_eight", include_str!("../scenarios/figure_eight.toml")),
+	("binary", include_str!("../scenarios/binary.toml")),
+	("sun", include_str!("../scenarios/sun.toml")),
+	("black_hole", include_str!("../scenarios/black_hole.toml")),
+];
+
+/// The celestial bodies of a planetary system, loaded from a TOML file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scenario {
+	#[serde(default)]
+	pub bodies: Vec<CelestialSpec>,
+}
+
+/// A celestial body at the start of a round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CelestialSpec {
+	#[serde(default)]
+	pub form: CelestialForm,
+	pub x: f32,
+	pub y: f32,
+	/// The radius of the event horizon for black holes.
+	#[serde(default = "default_radius")]
+	pub radius: f32,
+	/// Follows from `Tuning::celestial_density` if there is none.
+	pub mass: Option<f32>,
+	#[serde(default)]
+	pub velocity: [f32; 2],
+	/// Whether the body stays in place, neither pulled nor pushed by anything.
+	#[serde(default)]
+	pub fixed: bool,
+}
+
+fn default_radius() -> f32 {
+	CELESTIAL_RADIUS
+}
+
+impl Scenario {
+	/// The bundled scenario called `name`, or else the scenario in the file at `name`.
+	pub fn load(name: &str) -> Self {
+		let content = match BUNDLED.iter().find(|(bundled, _)| *bundled == name) {
+			Some((_, content)) => content.to_string(),
+			None => std::fs::read_to_string(name).expect("Cannot read scenario file."),
+		};
+		toml::from_str(&content).expect("Cannot parse scenario file.")
+	}
+
+	/// Names of the bundled scenarios.
+	pub fn bundled() -> impl Iterator<Item = &'static str> {
+		BUNDLED.iter().map(|(name, _)| *name)
+	}
+}
+
+impl CelestialSpec {
+	/// The mass of the body, given the density of bodies without one.
+	pub fn mass(&self, density: f32) -> f32 {
+		self.mass.unwrap_or_else(|| density * self.area())
+	}
+
+	pub fn area(&self) -> f32 {
+		std::f32::consts::PI * self.radius * self.radius
+	}
+}
@@ -1,24 +1,71 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::time::Instant;
 
 use actix::Addr;
 use bevy::ecs::entity::Entity;
 use futures::channel::mpsc::UnboundedSender;
 use futures::channel::oneshot::Sender;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
-use game_shared::PlayerState;
+use game_shared::{JoinInfo, PlayerState};
 
 use crate::event::GameEvent;
-use crate::WsSession;
+use crate::{WsSession, TICK_TIME};
 
 pub struct GameServer {
 	pub(crate) start_time: Instant,
 	pub(crate) sessions: HashMap<Entity, Addr<WsSession>>,
+	/// Players waiting for room in the arena, in the order they joined.
+	pub(crate) queue: VecDeque<PendingJoin>,
+	/// The number of the current round, starting from 1.
+	pub(crate) round: u32,
+	/// When the current round started and the last one ended, by the `GameClock`.
+	pub(crate) round_start: f64,
+	pub(crate) round_end: f64,
 }
 
 impl GameServer {
 	pub fn new() -> Self {
-		GameServer { start_time: Instant::now(), sessions: HashMap::new() }
+		GameServer {
+			start_time: Instant::now(),
+			sessions: HashMap::new(),
+			queue: VecDeque::new(),
+			round: 1,
+			round_start: 0.0,
+			round_end: 0.0,
+		}
+	}
+}
+
+/// A player waiting to join a full arena.
+pub(crate) struct PendingJoin {
+	pub(crate) info: JoinInfo,
+	pub(crate) sender: Sender<Entity>,
+	pub(crate) session: Addr<WsSession>,
+}
+
+/// The RNG placing everything in the world, so that the same seed and the same inputs
+/// yield the same world.
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+	pub fn new(seed: u64) -> Self {
+		GameRng(StdRng::seed_from_u64(seed))
+	}
+}
+
+/// The time of the game world, advancing by `TICK_TIME` every tick,
+/// so that timers depend on the ticks simulated rather than on how fast they run.
+#[derive(Default)]
+pub struct GameClock {
+	pub tick: u64,
+}
+
+impl GameClock {
+	/// Seconds of the world since it started.
+	pub fn now(&self) -> f64 {
+		self.tick as f64 * TICK_TIME.as_secs_f64()
 	}
 }
 
@@ -34,11 +81,11 @@ impl GameProxy {
 
 	pub fn create_player(
 		&mut self,
-		name: String,
+		info: JoinInfo,
 		sender: Sender<Entity>,
 		session: Addr<WsSession>,
 	) {
-		self.sender.unbounded_send(GameEvent::CreatePlayer(name, sender, session)).unwrap();
+		self.sender.unbounded_send(GameEvent::CreatePlayer(info, sender, session)).unwrap();
 	}
 
 	pub fn change_movement(&mut self, player: Option<Entity>, state: PlayerState) {
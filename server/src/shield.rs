@@ -0,0 +1,74 @@
+use bevy_rapier2d::rapier::geometry::ColliderShape;
+use bevy_rapier2d::rapier::math::{Isometry, Point};
+
+use game_shared::shield::{
+	ShieldType, ARC_RADIUS, ARC_SPAN, ARC_THICKNESS, BULWARK_HALF_EXTENTS, LIGHT_RADIUS,
+	SPIKE_LENGTH, SPIKE_WIDTH,
+};
+use game_shared::SHIELD_RADIUS;
+
+/// Segments approximating the arc of a wide shield.
+const ARC_SEGMENTS: usize = 4;
+
+/// How a shield archetype differs from the others, besides its shape.
+#[derive(Clone, Copy, Debug)]
+pub struct ShieldSpec {
+	/// Multiplies `Tuning::shield_density`.
+	pub density: f32,
+	pub dmg: i32,
+	/// Multiplies the distance limits of the shield from the body.
+	pub reach: f32,
+	/// Multiplies `Tuning::shield_motor_velocity`.
+	pub speed: f32,
+}
+
+impl ShieldSpec {
+	pub fn of(shield_type: ShieldType) -> Self {
+		let (density, dmg, reach, speed) = match shield_type {
+			ShieldType::Circle => (1.0, 1, 1.0, 1.0),
+			ShieldType::WideArc => (1.5, 1, 0.9, 0.8),
+			ShieldType::Spike => (1.0, 3, 1.15, 1.0),
+			ShieldType::Bulwark => (8.0, 1, 0.85, 0.6),
+			ShieldType::Light => (0.4, 1, 1.1, 1.6),
+		};
+		ShieldSpec { density, dmg, reach, speed }
+	}
+
+	/// Scale the distance `limits` of the shield from the body by the reach.
+	pub fn limits(&self, limits: [f32; 2]) -> [f32; 2] {
+		[limits[0] * self.reach, limits[1] * self.reach]
+	}
+}
+
+/// The collider of a shield, with the body of its player along the positive x axis.
+pub fn collider_shape(shield_type: ShieldType) -> ColliderShape {
+	match shield_type {
+		ShieldType::Circle => ColliderShape::ball(SHIELD_RADIUS),
+		ShieldType::WideArc => arc(),
+		ShieldType::Spike => ColliderShape::triangle(
+			Point::new(0.0, -SPIKE_WIDTH / 2.0),
+			Point::new(0.0, SPIKE_WIDTH / 2.0),
+			Point::new(-SPIKE_LENGTH, 0.0),
+		),
+		ShieldType::Bulwark => {
+			ColliderShape::cuboid(BULWARK_HALF_EXTENTS.0, BULWARK_HALF_EXTENTS.1)
+		}
+		ShieldType::Light => ColliderShape::ball(LIGHT_RADIUS),
+	}
+}
+
+/// Capsules along an arc centered on the side of the body, passing through the origin.
+fn arc() -> ColliderShape {
+	let point = |i: usize| {
+		let angle =
+			std::f32::consts::PI - ARC_SPAN / 2.0 + ARC_SPAN * i as f32 / ARC_SEGMENTS as f32;
+		Point::new(ARC_RADIUS + ARC_RADIUS * angle.cos(), ARC_RADIUS * angle.sin())
+	};
+	let segments = (0..ARC_SEGMENTS)
+		.map(|i| {
+			let capsule = ColliderShape::capsule(point(i), point(i + 1), ARC_THICKNESS / 2.0);
+			(Isometry::identity(), capsule)
+		})
+		.collect();
+	ColliderShape::compound(segments)
+}
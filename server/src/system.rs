@@ -1,51 +1,71 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use bevy::ecs::system::Command;
 use bevy::prelude::*;
+use bevy::tasks::ComputeTaskPool;
 use bevy_rapier2d::na::Vector2;
 use bevy_rapier2d::physics::{
 	ColliderBundle, JointBuilderComponent, JointHandleComponent, RapierConfiguration,
 	RigidBodyBundle, RigidBodyPositionSync,
 };
-use bevy_rapier2d::prelude::{RigidBodyCcd, IntoEntity, ColliderFlags, InteractionGroups};
+use bevy_rapier2d::prelude::{
+	ColliderFlags, ColliderType, InteractionGroups, IntoEntity, RigidBodyCcd, RigidBodyPosition,
+};
 use bevy_rapier2d::rapier::dynamics::{
-	JointParams, JointSet, PrismaticJoint, RigidBodyForces, RigidBodyMassProps, RigidBodyType,
-	RigidBodyVelocity,
+	JointParams, JointSet, PrismaticJoint, RigidBodyActivation, RigidBodyForces,
+	RigidBodyMassProps, RigidBodyType, RigidBodyVelocity,
 };
 use bevy_rapier2d::rapier::geometry::{ColliderMassProps, ColliderMaterial, ColliderShape};
 use bevy_rapier2d::rapier::na::Vector;
-use rand::prelude::ThreadRng;
+use rand::rngs::StdRng;
 use rand::Rng;
 
+use game_shared::replay::RecordedEvent;
 use game_shared::{
-	CelestialView, Ori, PlayerState, PlayerView, Position, ShieldView, StaticView, Status,
-	ViewSnapshot, CELESTIAL_RADIUS, INIT_RADIUS, MAP_HEIGHT, MAP_WIDTH, SHIELD_RADIUS, VIEW_X,
-	VIEW_Y,
+	CelestialView, Ori, PlayerState, PlayerView, Position, ScoreView, ServerMessage, ShieldView,
+	StaticView, Status, ViewSnapshot, INIT_RADIUS, MAX_ENERGY, VIEW_X, VIEW_Y,
 };
 
+use crate::barnes_hut::QuadTree;
 use crate::component::*;
+use crate::config::Config;
+use crate::damage::{self, Impactor};
+use crate::debris;
 use crate::event::{EventListener, GameEvent};
-use crate::server::GameServer;
-use crate::{View, WsSession};
+use crate::gravity::Attractor;
+use crate::map::Map;
+use crate::replay::Recorder;
+use crate::scenario::CelestialSpec;
+use crate::server::{GameClock, GameRng, GameServer, PendingJoin};
+use crate::shield::{self, ShieldSpec};
+use crate::{GameState, Push, WsSession, TICK_TIME};
 use actix::Addr;
 use futures::channel::oneshot::Sender;
 use bevy_rapier2d::rapier::pipeline::ActiveEvents;
 use bevy_rapier2d::rapier::prelude::ContactEvent;
 
 const INIT_MASS: f32 = 1.0;
-const INIT_RESTITUTION: f32 = 1.0;
-const INIT_DENSITY: f32 = 0.0008;
-
-const PLAYER_DENSITY: f32 = 0.0008;
-const SHIELD_DENSITY: f32 = 0.000008;
+pub(crate) const INIT_RESTITUTION: f32 = 1.0;
 
 const CELESTIAL_MASS: f32 = 10000000.0;
-const CELESTIAL_DENSITY: f32 = 318.3;
 
-const GRAVITY_CONST: f32 = 20.0;
+/// The client has colors and names for this many teams.
+pub const MAX_TEAMS: u32 = 4;
+
+/// Seconds within which a hit still counts towards a kill or an assist.
+const ASSIST_WINDOW: f64 = 10.0;
+const KILL_SCORE: i32 = 100;
+const ASSIST_SCORE: i32 = 50;
+const LEADERBOARD_SIZE: usize = 10;
+const SHIELD_HP: i32 = 100;
+/// Bodies pulled by gravity per parallel task.
+const GRAVITY_BATCH: usize = 256;
 
 /// TODO: generalize `create_[...]` as a trait?
 fn create_body(
 	commands: &mut Commands,
-	name: String,
+	player: Player,
 	x: f32,
 	y: f32,
 	rigid_body: RigidBodyBundle,
@@ -53,12 +73,16 @@ fn create_body(
 ) -> Entity {
 	commands
 		.spawn_bundle((
-			Player { name },
+			player,
 			Thrust { x: 0.0, y: 0.0 },
 			Ori { deg: 0.0, push: false },
 			Transform::from_translation(Vec3::new(x, y, 0.0)),
+			Material::Body,
 			Dmg { val: 1 },
 			HP { val: 100 },
+			Attackers { hits: Vec::new() },
+			Energy { val: MAX_ENERGY, boosting: false, last_boost: f64::NEG_INFINITY },
+			Score { kills: 0, assists: 0, deaths: 0, damage: 0, score: 0 },
 		))
 		.insert_bundle(rigid_body)
 		.insert_bundle(collider)
@@ -66,21 +90,26 @@ fn create_body(
 		.id()
 }
 
-/// Create a shield of `shield_type`.
+/// Create a shield of `shield_type` dealing `dmg`.
 fn create_shield(
 	commands: &mut Commands,
 	shield_type: ShieldType,
+	dmg: i32,
 	x: f32,
 	y: f32,
 	rigid_body: RigidBodyBundle,
 	collider: ColliderBundle,
 ) -> Entity {
+	let groups = collider.flags.collision_groups;
 	commands
 		.spawn_bundle((
 			shield_type,
 			Transform::from_translation(Vec3::new(x, y, 0.0)),
-			Dmg { val: 1 },
-			HP { val: 100 },
+			Material::Shield,
+			Dmg { val: dmg },
+			HP { val: SHIELD_HP },
+			ShieldState { broken: false, last_hit: f64::NEG_INFINITY, regen: 0.0, groups },
+			Bash { phase: BashPhase::Ready, requested: false, started: f64::NEG_INFINITY },
 		))
 		.insert_bundle(rigid_body)
 		.insert_bundle(collider)
@@ -89,7 +118,7 @@ fn create_shield(
 }
 
 /// Create a geometric objects with `shape`.
-fn create_object(
+pub(crate) fn create_object(
 	commands: &mut Commands,
 	shape: Shape,
 	x: f32,
@@ -101,6 +130,7 @@ fn create_object(
 		.spawn_bundle((
 			shape,
 			Transform::from_translation(Vec3::new(x, y, 0.0)),
+			Material::Debris,
 			Dmg { val: 1 },
 			HP { val: 100 },
 		))
@@ -115,6 +145,7 @@ fn create_seg_boundary(commands: &mut Commands, x: Vec2, y: Vec2) {
 	let entity = commands
 		.spawn_bundle((
 			Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+			Material::Boundary,
 			Dmg { val: 1 },
 			HP { val: 100 },
 		))
@@ -132,108 +163,275 @@ fn create_seg_boundary(commands: &mut Commands, x: Vec2, y: Vec2) {
 		.insert(Boundary { info: "Seg".to_string() });
 }
 
-/// Create a planet centered at (`x`, `y`) with `linvel`.
-fn create_planet(commands: &mut Commands, x: f32, y: f32, linvel: Vec2) {
+/// Create a static obstacle of `shape` at (`x`, `y`) rotated by `rot`.
+fn create_obstacle(commands: &mut Commands, shape: &Shape, x: f32, y: f32, rot: f32) {
 	let entity = commands
 		.spawn_bundle((
-			Thrust { x: 0.0, y: 0.0 },
-			Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+			Transform::from_translation(Vec3::new(x, y, 0.0)),
+			Material::Boundary,
 			Dmg { val: 1 },
 			HP { val: 100 },
 		))
 		.id();
 	let rigid_body = RigidBodyBundle {
-		position: Vec2::new(x, y).into(),
-		velocity: RigidBodyVelocity { linvel: linvel.into(), ..Default::default() },
+		body_type: RigidBodyType::Static,
+		position: (Vec2::new(x, y), rot).into(),
 		..Default::default()
 	};
 	let collider = ColliderBundle {
-		shape: ColliderShape::ball(CELESTIAL_RADIUS),
-		mass_properties: ColliderMassProps::Density(CELESTIAL_DENSITY),
+		shape: debris::collider_shape(shape),
+		material: ColliderMaterial { restitution: INIT_RESTITUTION, ..Default::default() },
+		..Default::default()
+	};
+	commands
+		.entity(entity)
+		.insert_bundle(rigid_body)
+		.insert_bundle(collider)
+		.insert(Boundary { info: "Obstacle".to_string() });
+}
+
+/// Create a celestial body as given by `spec`, with `density` unless its mass is given.
+pub(crate) fn create_planet(commands: &mut Commands, spec: &CelestialSpec, density: f32) {
+	let mass = spec.mass(density);
+	let entity = commands
+		.spawn_bundle((
+			Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+			Material::Planet,
+			Dmg { val: 1 },
+			HP { val: 100 },
+		))
+		.id();
+	// Celestial bodies orbit by their own gravity and push anything else out of their way.
+	let body_type =
+		if spec.fixed { RigidBodyType::Static } else { RigidBodyType::KinematicPositionBased };
+	let rigid_body = RigidBodyBundle {
+		body_type,
+		position: Vec2::new(spec.x, spec.y).into(),
+		activation: RigidBodyActivation::cannot_sleep(),
+		..Default::default()
+	};
+	let mut collider = ColliderBundle {
+		shape: ColliderShape::ball(spec.radius),
+		mass_properties: ColliderMassProps::Density(mass / spec.area()),
 		material: ColliderMaterial { restitution: INIT_RESTITUTION, ..Default::default() },
 		..Default::default()
 	};
+	// Nothing bounces off a black hole, it just senses what crosses the event horizon.
+	if spec.form == CelestialForm::BlackHole {
+		collider.collider_type = ColliderType::Sensor;
+		collider.flags.active_events = ActiveEvents::INTERSECTION_EVENTS;
+	}
 	commands
 		.entity(entity)
 		.insert_bundle(rigid_body)
 		.insert_bundle(collider)
 		.insert(RigidBodyPositionSync::Discrete)
-		.insert(CelestialBody { form: "planet".to_string() });
+		.insert(CelestialBody { form: spec.form, radius: spec.radius, mass, fixed: spec.fixed })
+		.insert(Orbit {
+			pos: Vector2::new(spec.x as f64, spec.y as f64),
+			vel: Vector2::new(spec.velocity[0] as f64, spec.velocity[1] as f64),
+		});
 }
 
 /// Basic setup at the beginning.
-pub fn setup(mut commands: Commands, mut configuration: ResMut<RapierConfiguration>) {
-	let mut rng = rand::thread_rng();
+pub fn setup(
+	mut commands: Commands,
+	mut configuration: ResMut<RapierConfiguration>,
+	config: Res<Config>,
+	map: Res<Map>,
+	mut rng: ResMut<GameRng>,
+) {
+	let rng = &mut rng.0;
+	let tuning = &config.tuning;
 
 	// Disable gravity.
 	configuration.gravity = Vector2::new(0.0, 0.0);
 
 	// Create Boundaries.
-	create_seg_boundary(&mut commands, Vec2::new(0.0, 0.0), Vec2::new(MAP_WIDTH, 0.0));
-	create_seg_boundary(&mut commands, Vec2::new(0.0, 0.0), Vec2::new(0.0, MAP_HEIGHT));
-	create_seg_boundary(&mut commands, Vec2::new(MAP_WIDTH, 0.0), Vec2::new(MAP_WIDTH, MAP_HEIGHT));
-	create_seg_boundary(
-		&mut commands,
-		Vec2::new(0.0, MAP_HEIGHT),
-		Vec2::new(MAP_WIDTH, MAP_HEIGHT),
-	);
+	for boundary in map.boundaries.iter() {
+		for segment in boundary.points.windows(2) {
+			let (a, b) = (segment[0], segment[1]);
+			create_seg_boundary(&mut commands, Vec2::new(a[0], a[1]), Vec2::new(b[0], b[1]));
+		}
+	}
+	for obstacle in map.obstacles.iter() {
+		create_obstacle(&mut commands, &obstacle.shape, obstacle.x, obstacle.y, obstacle.rot);
+	}
 
 	// Add Random stuffs.
-	for _ in 0..100 {
-		let x = rng.gen_range(0.4 * MAP_WIDTH..0.6 * MAP_WIDTH);
-		let y = rng.gen_range(0.4 * MAP_HEIGHT..0.6 * MAP_HEIGHT);
-		let rigid_body = RigidBodyBundle {
-			position: Vec2::new(x, y).into(),
-			ccd: RigidBodyCcd { ccd_enabled: true, ..Default::default() },
-			..Default::default()
-		};
-		let collider = ColliderBundle {
-			shape: ColliderShape::ball(INIT_RADIUS),
-			mass_properties: ColliderMassProps::Density(INIT_DENSITY),
-			material: ColliderMaterial { restitution: INIT_RESTITUTION, ..Default::default() },
-			..Default::default()
-		};
-		create_object(&mut commands, Shape::Circle, x, y, rigid_body, collider);
+	for _ in 0..config.debris_count {
+		let (x, y) = map.debris_point(rng);
+		let rot = rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI);
+		let shape = debris::random_shape(rng);
+		let velocity = RigidBodyVelocity::default();
+		debris::spawn_debris(&mut commands, shape, x, y, rot, velocity, &config);
 	}
 
 	// Add Celestial objects.
-	create_planet(&mut commands, 4029.99564, 5243.08753, Vec2::new(46.62036850, 43.23657300));
-	create_planet(&mut commands, 5000.0, 5000.0, Vec2::new(-93.240737, -86.473146));
-	create_planet(&mut commands, 5970.00436, 4756.91247, Vec2::new(46.62036850, 43.23657300));
+	for spec in map.celestial_bodies(config.scenario.as_deref()).iter() {
+		create_planet(&mut commands, spec, tuning.celestial_density);
+	}
 }
 
 pub fn handle_events(
 	mut commands: Commands,
+	config: Res<Config>,
+	map: Res<Map>,
 	mut game_state: ResMut<GameServer>,
 	mut events: ResMut<EventListener>,
+	mut rng: ResMut<GameRng>,
+	recorder: Option<ResMut<Recorder>>,
+	shield_ids: Query<&ShieldID>,
+	joints: Query<(Entity, &JointHandleComponent)>,
+	players: Query<&Player>,
 ) {
-	let mut rng = rand::thread_rng();
+	let mut recorded = Vec::new();
+	let mut queue_changed = false;
 	for event in events.drain() {
 		match event {
-			GameEvent::CreatePlayer(name, sender, session) => {
-				create_player(&mut commands, name, sender, session, &mut rng, &mut *game_state)
+			GameEvent::CreatePlayer(info, sender, session) => {
+				game_state.queue.push_back(PendingJoin { info, sender, session });
+				queue_changed = true;
 			}
 			GameEvent::RemovePlayer(player) => {
-				commands.entity(player).despawn();
+				game_state.sessions.remove(&player);
+				despawn_player(&mut commands, player, &shield_ids, &joints);
+				recorded.push(RecordedEvent::Leave(player.to_bits()));
 			}
 			GameEvent::UpdatePlayer(player, state) => {
+				if recorder.is_some() {
+					recorded.push(RecordedEvent::Update(player.to_bits(), state.clone()));
+				}
 				commands.add(ChangeMovement { player, state });
 			}
 		}
 	}
+
+	// Admit waiting players while there is room and tell the others their place in the queue.
+	let waiting = game_state.queue.len();
+	game_state.queue.retain(|pending| pending.session.connected());
+	queue_changed |= game_state.queue.len() != waiting;
+	let mut team_sizes = team_sizes(config.teams, &players);
+	while config.max_players.map_or(true, |max| game_state.sessions.len() < max) {
+		let PendingJoin { info, sender, session } = match game_state.queue.pop_front() {
+			Some(pending) => pending,
+			None => break,
+		};
+		let team = pick_team(info.team, &mut team_sizes);
+		let player = create_player(
+			&mut commands,
+			Player { name: info.name.clone(), team },
+			info.shield,
+			sender,
+			session,
+			&mut rng.0,
+			&mut *game_state,
+			&config,
+			&map,
+		);
+		if let Some(player) = player {
+			recorded.push(RecordedEvent::Join(player.to_bits(), info));
+		}
+		queue_changed = true;
+	}
+	if queue_changed {
+		for (i, pending) in game_state.queue.iter().enumerate() {
+			pending.session.do_send(Push(ServerMessage::Queued(i as u32 + 1)));
+		}
+	}
+
+	if let Some(mut recorder) = recorder {
+		recorder.record_tick(recorded);
+	}
+}
+
+/// Despawn `player` along with its shield and the joint between them.
+pub(crate) fn despawn_player(
+	commands: &mut Commands,
+	player: Entity,
+	shield_ids: &Query<&ShieldID>,
+	joints: &Query<(Entity, &JointHandleComponent)>,
+) {
+	if let Ok(shield_id) = shield_ids.get(player) {
+		commands.entity(shield_id.entity).despawn();
+	}
+	for (joint, joint_hc) in joints.iter() {
+		if joint_hc.entity1() == player {
+			commands.entity(joint).despawn();
+		}
+	}
+	commands.entity(player).despawn();
+}
+
+/// The number of players in each of the `teams`.
+pub(crate) fn team_sizes(teams: u32, players: &Query<&Player>) -> Vec<usize> {
+	let mut sizes = vec![0; teams as usize];
+	for team in players.iter().filter_map(|player| player.team) {
+		sizes[team as usize] += 1;
+	}
+	sizes
 }
 
-/// Create players for a stream of [CreatePlayer] `events`.
+/// Pick the `requested` team if there is such a team, or otherwise the one with the fewest
+/// players, and count the joining player in `team_sizes`. There are no teams if it is empty.
+pub(crate) fn pick_team(requested: Option<u32>, team_sizes: &mut [usize]) -> Option<u32> {
+	let teams = team_sizes.len() as u32;
+	let team = requested
+		.filter(|team| *team < teams)
+		.or_else(|| (0..teams).min_by_key(|team| team_sizes[*team as usize]))?;
+	team_sizes[team as usize] += 1;
+	Some(team)
+}
+
+/// Interaction groups of the body and the shield of a player in `team`.
+/// Teammates pass through each other unless `friendly_collision` is on.
+fn player_groups(team: Option<u32>, friendly_collision: bool) -> InteractionGroups {
+	match team {
+		Some(team) if !friendly_collision => {
+			let group = 0b10 << team;
+			InteractionGroups::new(group, !group)
+		}
+		_ => InteractionGroups::new(0b01, 0b01),
+	}
+}
+
+/// Create a player for `session` and send its entity through `sender`.
+/// Nothing is left of the player if the session has closed in the meantime.
 fn create_player(
 	commands: &mut Commands,
-	name: String,
+	player: Player,
+	shield_type: ShieldType,
 	sender: Sender<Entity>,
 	session: Addr<WsSession>,
-	rng: &mut ThreadRng,
+	rng: &mut StdRng,
 	game_state: &mut GameServer,
-) {
-	let x = rng.gen_range(0.4 * MAP_WIDTH..0.6 * MAP_WIDTH);
-	let y = rng.gen_range(0.4 * MAP_HEIGHT..0.6 * MAP_HEIGHT);
+	config: &Config,
+	map: &Map,
+) -> Option<Entity> {
+	let entity_body = spawn_player(commands, player, shield_type, rng, config, map);
+	session.do_send(Push(ServerMessage::Map(map.view())));
+	game_state.sessions.insert(entity_body, session);
+	if sender.send(entity_body).is_err() {
+		game_state.sessions.remove(&entity_body);
+		commands.add(Unspawn { player: entity_body });
+		return None;
+	}
+	Some(entity_body)
+}
+
+/// Spawn the body and a shield of `shield_type` of a player at a random spawn point of `map`.
+pub fn spawn_player(
+	commands: &mut Commands,
+	player: Player,
+	shield_type: ShieldType,
+	rng: &mut StdRng,
+	config: &Config,
+	map: &Map,
+) -> Entity {
+	let (x, y) = map.spawn_point(rng);
+	let tuning = &config.tuning;
+	let groups = player_groups(player.team, config.friendly_collision);
+	let name = player.name.clone();
 
 	// The entity of player's body.
 	let rigid_body = RigidBodyBundle {
@@ -243,18 +441,19 @@ fn create_player(
 	};
 	let collider = ColliderBundle {
 		shape: ColliderShape::ball(INIT_RADIUS),
-		mass_properties: ColliderMassProps::Density(PLAYER_DENSITY),
+		mass_properties: ColliderMassProps::Density(tuning.player_density),
 		material: ColliderMaterial { restitution: INIT_RESTITUTION, ..Default::default() },
 		flags: ColliderFlags {
-			collision_groups: InteractionGroups::new(0b01, 0b01),
+			collision_groups: groups,
 			active_events: ActiveEvents::CONTACT_EVENTS,
 			..Default::default()
 		},
 		..Default::default()
 	};
-	let entity_body = create_body(commands, name.clone(), x, y, rigid_body, collider);
+	let entity_body = create_body(commands, player, x, y, rigid_body, collider);
 
 	// The entity of shield.
+	let spec = ShieldSpec::of(shield_type);
 	let x_shield = x + 40.0;
 	let y_shield = y;
 	let rigid_body = RigidBodyBundle {
@@ -263,18 +462,18 @@ fn create_player(
 		..Default::default()
 	};
 	let collider = ColliderBundle {
-		shape: ColliderShape::ball(SHIELD_RADIUS),
-		mass_properties: ColliderMassProps::Density(SHIELD_DENSITY),
+		shape: shield::collider_shape(shield_type),
+		mass_properties: ColliderMassProps::Density(tuning.shield_density * spec.density),
 		material: ColliderMaterial { restitution: INIT_RESTITUTION, ..Default::default() },
 		flags: ColliderFlags {
-			collision_groups: InteractionGroups::new(0b01, 0b01),
+			collision_groups: groups,
 			active_events: ActiveEvents::CONTACT_EVENTS,
 			..Default::default()
 		},
 		..Default::default()
 	};
 	let entity_shield =
-		create_shield(commands, ShieldType::Circle, x_shield, y_shield, rigid_body, collider);
+		create_shield(commands, shield_type, spec.dmg, x_shield, y_shield, rigid_body, collider);
 
 	commands.entity(entity_body).insert(ShieldID { entity: entity_shield });
 
@@ -282,12 +481,11 @@ fn create_player(
 	let x = Vector::x_axis();
 	let mut joint = PrismaticJoint::new(Vec2::ZERO.into(), x, Vec2::new(0.0, 0.0).into(), x);
 	// The shield is limited to 20~80 px away from the body.
-	joint.limits = [-80.0, -60.0];
+	joint.limits = spec.limits([tuning.shield_push_limits[0], tuning.shield_limits[0]]);
 	commands.spawn().insert(JointBuilderComponent::new(joint, entity_body, entity_shield));
 
-	game_state.sessions.insert(entity_body, session.clone());
-	sender.send(entity_body).unwrap();
 	println!("Player {} (#{} #{}) joined the game.", name, entity_body.id(), entity_shield.id());
+	entity_body
 }
 
 /// Rotate shields towards the cursor's position `Ori.deg`.
@@ -330,22 +528,49 @@ pub fn rotate_shield(
 	}
 }
 
+/// Move shields within the limits of resting, being pushed or bashing,
+/// and start the bashes of players asking for one.
 pub fn push_shield(
+	config: Res<Config>,
+	clock: Res<GameClock>,
 	mut joint_set: ResMut<JointSet>,
 	players: Query<(&Ori), With<Player>>,
+	mut shields: Query<(&ShieldType, &ShieldState, &mut Bash)>,
 	joints: Query<(&JointHandleComponent)>,
 ) {
+	let tuning = &config.tuning;
+	let now = clock.now();
 	for (joint_hc) in joints.iter() {
 		let ori = players.get(joint_hc.entity1()).unwrap();
+		let (shield_type, state, mut bash) = shields.get_mut(joint_hc.entity2()).unwrap();
+		let spec = ShieldSpec::of(*shield_type);
+		let since_bash = now - bash.started;
+		bash.phase = if since_bash < tuning.bash_secs {
+			BashPhase::Active
+		} else if since_bash < tuning.bash_secs + tuning.bash_cooldown {
+			BashPhase::Cooldown
+		} else {
+			BashPhase::Ready
+		};
+		if bash.phase == BashPhase::Ready && bash.requested && !state.broken {
+			bash.phase = BashPhase::Active;
+			bash.started = now;
+		}
+
 		let joint = joint_set.get_mut(joint_hc.handle()).unwrap();
 		match &mut joint.params {
 			JointParams::PrismaticJoint(prismatic_joint) => {
-				if ori.push {
-					prismatic_joint.limits = [-80.0, -70.0];
-					prismatic_joint.configure_motor_velocity(-300.0, 0.1);
+				let motor_velocity = tuning.shield_motor_velocity * spec.speed;
+				if bash.phase == BashPhase::Active {
+					prismatic_joint.limits = spec.limits(tuning.shield_bash_limits);
+					prismatic_joint
+						.configure_motor_velocity(tuning.shield_bash_velocity * spec.speed, 0.1);
+				} else if ori.push {
+					prismatic_joint.limits = spec.limits(tuning.shield_push_limits);
+					prismatic_joint.configure_motor_velocity(motor_velocity, 0.1);
 				} else {
-					prismatic_joint.limits = [-60.0, -55.0];
-					prismatic_joint.configure_motor_velocity(-300.0, 0.1);
+					prismatic_joint.limits = spec.limits(tuning.shield_limits);
+					prismatic_joint.configure_motor_velocity(motor_velocity, 0.1);
 				}
 			}
 			_ => panic!(),
@@ -353,156 +578,638 @@ pub fn push_shield(
 	}
 }
 
-/// Simulate gravitational forces exerted by `celestial_bodies` on `object_bodies`.
-/// TODO: include both `Player` and `Shape` (the performance behaves strangely?) and remove Thrust.
-pub fn simulate(
-	celestial_bodies: Query<(&Transform, &RigidBodyMassProps), With<CelestialBody>>,
-	mut object_bodies: Query<
-		(&Thrust, &Transform, &mut RigidBodyForces, &RigidBodyMassProps),
-		With<Player>,
-	>, /*mut object_bodies: Query<(&Thrust, &Transform, &mut RigidBodyForces, &RigidBodyMassProps), Or<(With<Player>, With<Shape>, With<CelestialBody>)>>*/
+/// Boost players holding the boost input in their direction of movement
+/// while they have the energy, and regenerate the energy of everyone.
+pub fn boost(
+	config: Res<Config>,
+	clock: Res<GameClock>,
+	mut players: Query<(&Thrust, &mut Energy, &mut RigidBodyVelocity, &RigidBodyMassProps)>,
 ) {
-	for (thrust, obj_transform, mut obj_forces, obj_mprops) in object_bodies.iter_mut() {
-		let mut forces = Vector2::new(thrust.x, thrust.y);
-		let obj_mass = 1.0 / obj_mprops.local_mprops.inv_mass;
-		// Compute gravitational forces.
-		for (cb_transform, cb_mprops) in celestial_bodies.iter() {
-			let cb_mass = 1.0 / cb_mprops.local_mprops.inv_mass;
-			let disp3 = cb_transform.translation - obj_transform.translation;
-			let disp2: Vector2<f32> = Vector2::new(disp3.x, disp3.y);
-			if disp2.norm() == 0.0 {
-				continue;
-			}
-			forces += GRAVITY_CONST * cb_mass * obj_mass * disp2 / disp2.norm().powi(3);
+	let tuning = &config.tuning;
+	let now = clock.now();
+	let elapsed = TICK_TIME.as_secs_f32();
+	for (thrust, mut energy, mut velocity, mprops) in players.iter_mut() {
+		energy.val = (energy.val + tuning.energy_regen_rate * elapsed).min(MAX_ENERGY);
+		let dir = Vector2::new(thrust.x, thrust.y);
+		if !energy.boosting
+			|| dir.norm() == 0.0
+			|| energy.val < tuning.boost_cost
+			|| now - energy.last_boost < tuning.boost_cooldown
+		{
+			continue;
 		}
-		// Apply forces.
-		obj_forces.force = forces;
+		velocity.apply_impulse(mprops, dir.normalize() * tuning.boost_impulse);
+		energy.val -= tuning.boost_cost;
+		energy.last_boost = now;
 	}
 }
 
+/// Pull every dynamic body other than celestial bodies towards the celestial bodies,
+/// and push players by their thrust.
+/// The pull is approximated with a Barnes-Hut quadtree of the celestial bodies, in parallel.
+pub fn simulate(
+	config: Res<Config>,
+	pool: Res<ComputeTaskPool>,
+	celestial_bodies: Query<(&Transform, &CelestialBody)>,
+	mut bodies: Query<
+		(&Transform, &RigidBodyMassProps, &mut RigidBodyForces, Option<&Thrust>),
+		Without<CelestialBody>,
+	>,
+) {
+	let attractors: Vec<Attractor> = celestial_bodies
+		.iter()
+		.map(|(transform, cb)| Attractor {
+			pos: Vec2::new(transform.translation.x, transform.translation.y),
+			mass: cb.mass,
+		})
+		.collect();
+	let tree = QuadTree::new(&attractors);
+	let tuning = &config.tuning;
+	bodies.par_for_each_mut(&pool, GRAVITY_BATCH, |(transform, mprops, mut forces, thrust)| {
+		// Static bodies like boundaries don't move anyway, and bodies spawned this tick
+		// get their mass once the physics world sets them up.
+		let inv_mass = mprops.local_mprops.inv_mass;
+		if inv_mass == 0.0 {
+			return;
+		}
+		let pos = Vec2::new(transform.translation.x, transform.translation.y);
+		let accel = tree.acceleration(pos, tuning.gravity_const, tuning.gravity_opening_angle);
+		let pull = accel / inv_mass;
+		let mut force = Vector2::new(pull.x, pull.y);
+		if let Some(thrust) = thrust {
+			force += Vector2::new(thrust.x, thrust.y);
+		}
+		forces.force = force;
+	});
+}
+
+/// Apply damage between contacting objects from the impulse of their collision,
+/// and record which players dealt it.
+/// Hits on or by a shield are credited to the shield's owner, whose mass is behind the shield.
+/// Teammates don't damage each other unless friendly fire is on.
 pub fn compute_dmg(
+	mut commands: Commands,
+	config: Res<Config>,
+	clock: Res<GameClock>,
 	mut contact_events: EventReader<ContactEvent>,
-	dmg_query: Query<(&Dmg)>,
-	mut hp_query: Query<(&mut HP)>
+	bodies: Query<(&Dmg, &Material, &RigidBodyVelocity, &RigidBodyMassProps)>,
+	mut hp_query: Query<(&mut HP)>,
+	shield_ids: Query<(Entity, &ShieldID)>,
+	mut attackers: Query<&mut Attackers>,
+	mut scores: Query<&mut Score>,
+	mut shield_states: Query<&mut ShieldState>,
+	players: Query<&Player>,
+	bashes: Query<&Bash>,
+	transforms: Query<&Transform>,
 ) {
+	let owners: HashMap<Entity, Entity> =
+		shield_ids.iter().map(|(player, shield_id)| (shield_id.entity, player)).collect();
+	let owner = |entity: Entity| owners.get(&entity).copied().unwrap_or(entity);
+	let team = |entity: Entity| players.get(owner(entity)).ok().and_then(|player| player.team);
+	let impactor = |entity: Entity| {
+		let (dmg, material, velocity, mprops) = bodies.get(entity).ok()?;
+		let mut inv_mass = mprops.local_mprops.inv_mass;
+		if owner(entity) != entity {
+			let (_, _, _, owner_mprops) = bodies.get(owner(entity)).ok()?;
+			inv_mass = damage::combined_inv_mass(inv_mass, owner_mprops.local_mprops.inv_mass);
+		}
+		let linvel = Vec2::new(velocity.linvel.x, velocity.linvel.y);
+		Some(Impactor { material: *material, inv_mass, linvel, dmg: dmg.val })
+	};
+	let bashing =
+		|entity: Entity| bashes.get(entity).map_or(false, |bash| bash.phase == BashPhase::Active);
+	let tuning = &config.tuning;
+	let now = clock.now();
 	for contact_event in contact_events.iter() {
 		if let ContactEvent::Started(h1, h2) = contact_event {
-			let mut hp1 = hp_query.get_mut(h1.entity()).unwrap();
-			let dmg2 = dmg_query.get(h2.entity()).unwrap();
-			hp1.val -= dmg2.val;
-			let mut hp2 = hp_query.get_mut(h2.entity()).unwrap();
-			let dmg1 = dmg_query.get(h1.entity()).unwrap();
-			hp2.val -= dmg1.val;
+			let (e1, e2) = (h1.entity(), h2.entity());
+			// Shattered debris may still be in contacts from before it was despawned.
+			let (impactor1, impactor2) = match (impactor(e1), impactor(e2)) {
+				(Some(impactor1), Some(impactor2)) => (impactor1, impactor2),
+				_ => continue,
+			};
+			if !config.friendly_fire
+				&& owner(e1) != owner(e2)
+				&& team(e1).is_some()
+				&& team(e1) == team(e2)
+			{
+				continue;
+			}
+			let (mut dmg1, mut dmg2) =
+				damage::contact_damage(&impactor1, &impactor2, tuning.damage_per_impulse);
+			if bashing(e2) {
+				dmg1 = (dmg1 as f32 * tuning.bash_dmg_multiplier).round() as i32;
+			}
+			if bashing(e1) {
+				dmg2 = (dmg2 as f32 * tuning.bash_dmg_multiplier).round() as i32;
+			}
+
+			// Both sides of a damaging hit are in combat and their shields stop regenerating.
+			if dmg1 > 0 || dmg2 > 0 {
+				for entity in [e1, e2].iter() {
+					let shield = shield_ids.get(owner(*entity)).map(|(_, id)| id.entity);
+					if let Ok(mut state) = shield.and_then(|shield| shield_states.get_mut(shield)) {
+						state.last_hit = now;
+					}
+				}
+			}
+
+			let hits = [(e1, e2, dmg1), (e2, e1, dmg2)];
+			for (victim, attacker, dmg) in hits.iter().copied() {
+				// A bashing shield knocks back whatever it hits, along with its player.
+				if bashing(attacker) && owner(victim) != owner(attacker) {
+					let from = transforms.get(attacker).expect("No component found.").translation;
+					let to = transforms.get(victim).expect("No component found.").translation;
+					let dir = Vector2::new(to.x - from.x, to.y - from.y)
+						.try_normalize(f32::EPSILON)
+						.unwrap_or_else(Vector2::zeros);
+					let impulse = dir * tuning.bash_knockback;
+					commands.add(Knockback { entity: owner(victim), impulse });
+				}
+				if dmg <= 0 {
+					continue;
+				}
+				// A shield breaking passes the damage beyond its HP on to its player.
+				let mut excess = 0;
+				if let Ok(mut hp) = hp_query.get_mut(victim) {
+					if owner(victim) != victim {
+						excess = (dmg - hp.val.max(0)).max(0);
+					}
+					hp.val -= dmg - excess;
+				}
+				if excess > 0 {
+					if let Ok(mut hp) = hp_query.get_mut(owner(victim)) {
+						hp.val -= excess;
+					}
+				}
+				record_hit(&mut attackers, owner(victim), owner(attacker), now);
+				credit_damage(&mut scores, owner(victim), owner(attacker), dmg);
+			}
 		}
 	}
 }
 
+/// Record that `attacker` hit `victim` at `now` if both of them are players.
+fn record_hit(attackers: &mut Query<&mut Attackers>, victim: Entity, attacker: Entity, now: f64) {
+	if victim == attacker || attackers.get_mut(attacker).is_err() {
+		return;
+	}
+	if let Ok(mut victim_attackers) = attackers.get_mut(victim) {
+		victim_attackers.hits.retain(|(entity, _)| *entity != attacker);
+		victim_attackers.hits.push((attacker, now));
+	}
+}
+
+/// Add `dmg` to the damage dealt by `attacker` if it hit another player or the player's shield.
+fn credit_damage(scores: &mut Query<&mut Score>, victim: Entity, attacker: Entity, dmg: i32) {
+	if victim == attacker || scores.get_mut(victim).is_err() {
+		return;
+	}
+	if let Ok(mut score) = scores.get_mut(attacker) {
+		score.damage += dmg;
+	}
+}
+
+/// Respawn players running out of HP and credit the kill and assists to their attackers.
+pub fn handle_deaths(
+	mut commands: Commands,
+	clock: Res<GameClock>,
+	map: Res<Map>,
+	mut rng: ResMut<GameRng>,
+	mut players: Query<(Entity, &HP, &mut Attackers, &mut Score), With<Player>>,
+) {
+	let now = clock.now();
+	let mut credits = Vec::new();
+	for (entity, hp, mut attackers, mut score) in players.iter_mut() {
+		if hp.val > 0 {
+			continue;
+		}
+		score.deaths += 1;
+
+		// The last attacker gets the kill and the others get assists.
+		let mut recent: Vec<Entity> = attackers
+			.hits
+			.drain(..)
+			.filter(|(_, time)| now - time < ASSIST_WINDOW)
+			.map(|(entity, _)| entity)
+			.collect();
+		if let Some(killer) = recent.pop() {
+			credits.push((killer, recent));
+		}
+
+		let (x, y) = map.spawn_point(&mut rng.0);
+		commands.add(Respawn { player: entity, x, y });
+	}
+
+	for (killer, assistants) in credits {
+		if let Ok((_, _, _, mut score)) = players.get_mut(killer) {
+			score.kills += 1;
+			score.score += KILL_SCORE;
+		}
+		for assistant in assistants {
+			if let Ok((_, _, _, mut score)) = players.get_mut(assistant) {
+				score.assists += 1;
+				score.score += ASSIST_SCORE;
+			}
+		}
+	}
+}
+
+/// Collect the scores of all players, highest first.
+fn standings(players: &Query<(&Player, &Score)>) -> Vec<ScoreView> {
+	let mut standings: Vec<ScoreView> = players
+		.iter()
+		.map(|(player, score)| ScoreView {
+			name: player.name.clone(),
+			team: player.team,
+			kills: score.kills,
+			assists: score.assists,
+			deaths: score.deaths,
+			score: score.score,
+		})
+		.collect();
+	standings.sort_by(|a, b| b.score.cmp(&a.score).then(a.deaths.cmp(&b.deaths)));
+	standings
+}
+
+/// End the round once time runs out or the win condition is met.
+pub fn check_round_end(
+	config: Res<Config>,
+	clock: Res<GameClock>,
+	mut game_state: ResMut<GameServer>,
+	mut state: ResMut<State<GameState>>,
+	mut rapier_config: ResMut<RapierConfiguration>,
+	players: Query<(&Player, &Score)>,
+) {
+	let time_up = clock.now() - game_state.round_start >= config.round_duration().as_secs_f64();
+	let won = players.iter().any(|(_, score)| config.win_condition.is_met(score));
+	if !time_up && !won {
+		return;
+	}
+	if state.set(GameState::GameOver).is_err() {
+		return;
+	}
+
+	// Freeze the world and announce the final standings.
+	rapier_config.physics_pipeline_active = false;
+	game_state.round_end = clock.now();
+	let standings = standings(&players);
+	println!(
+		"Round {} is over. Winner: {}.",
+		game_state.round,
+		standings.first().map_or("nobody", |winner| winner.name.as_str())
+	);
+	for session in game_state.sessions.values() {
+		session.do_send(Push(ServerMessage::GameOver(standings.clone())));
+	}
+}
+
+/// Start a new round after the results have been shown for a while.
+pub fn check_round_start(
+	config: Res<Config>,
+	clock: Res<GameClock>,
+	game_state: Res<GameServer>,
+	mut state: ResMut<State<GameState>>,
+) {
+	if clock.now() - game_state.round_end >= config.results_duration().as_secs_f64() {
+		let _ = state.set(GameState::Playing);
+	}
+}
+
+/// Clear the world of the last round so that `setup` can build a new one, and respawn players.
+pub fn reset_world(
+	mut commands: Commands,
+	clock: Res<GameClock>,
+	mut game_state: ResMut<GameServer>,
+	mut rapier_config: ResMut<RapierConfiguration>,
+	map: Res<Map>,
+	mut rng: ResMut<GameRng>,
+	objects: Query<Entity, Or<(With<Shape>, With<CelestialBody>, With<Boundary>)>>,
+	mut players: Query<(Entity, &mut Attackers, &mut Score), With<Player>>,
+) {
+	for entity in objects.iter() {
+		commands.entity(entity).despawn();
+	}
+	for (entity, mut attackers, mut score) in players.iter_mut() {
+		attackers.hits.clear();
+		*score = Score { kills: 0, assists: 0, deaths: 0, damage: 0, score: 0 };
+		let (x, y) = map.spawn_point(&mut rng.0);
+		commands.add(Respawn { player: entity, x, y });
+	}
+
+	rapier_config.physics_pipeline_active = true;
+	game_state.round += 1;
+	game_state.round_start = clock.now();
+	for session in game_state.sessions.values() {
+		session.do_send(Push(ServerMessage::RoundStart));
+	}
+}
+
+/// Advance the clock of the world by a tick.
+pub fn advance_clock(mut clock: ResMut<GameClock>) {
+	clock.tick += 1;
+}
+
+/// Send the top players to every session.
+pub fn broadcast_leaderboard(
+	config: Res<Config>,
+	game_state: Res<GameServer>,
+	players: Query<(&Player, &Score)>,
+) {
+	let mut leaderboard = standings(&players);
+	leaderboard.truncate(LEADERBOARD_SIZE);
+	let mut team_scores = vec![0; config.teams as usize];
+	for (player, score) in players.iter() {
+		if let Some(team) = player.team {
+			team_scores[team as usize] += score.score;
+		}
+	}
+
+	for session in game_state.sessions.values() {
+		session.do_send(Push(ServerMessage::Leaderboard(leaderboard.clone())));
+		if config.teams > 0 {
+			session.do_send(Push(ServerMessage::TeamScores(team_scores.clone())));
+		}
+	}
+}
+
+/// Break shields running out of HP, and regenerate shields that have been out of combat
+/// for a while, mending broken ones once they are full again.
 pub fn restore_hp(
-	mut players: Query<(&Dmg, &mut HP, &ShieldID)>,
-	mut shields: Query<(&Dmg, &mut HP), (With<ShieldType>, Without<ShieldID>)>
+	config: Res<Config>,
+	clock: Res<GameClock>,
+	mut shields: Query<(&mut HP, &mut ShieldState, &mut ColliderFlags)>,
 ) {
-	// for (player_dmg, mut player_hp, shield_id) in players.iter_mut() {
-	// 	let (shield_dmg, mut shield_hp) = shields.get_mut(shield_id.entity).unwrap();
-	// 	shield_hp.val += player_dmg.val;
-	// 	player_hp.val += shield_dmg.val;
-	// }
+	let tuning = &config.tuning;
+	let now = clock.now();
+	let elapsed = TICK_TIME.as_secs_f32();
+	for (mut hp, mut state, mut flags) in shields.iter_mut() {
+		if hp.val <= 0 && !state.broken {
+			hp.val = 0;
+			set_broken(&mut state, &mut flags, true);
+		}
+		if hp.val >= SHIELD_HP || now - state.last_hit < tuning.shield_regen_delay {
+			state.regen = 0.0;
+			continue;
+		}
+		state.regen += tuning.shield_regen_rate * elapsed;
+		let restored = state.regen.floor();
+		state.regen -= restored;
+		hp.val = (hp.val + restored as i32).min(SHIELD_HP);
+		if state.broken && hp.val == SHIELD_HP {
+			set_broken(&mut state, &mut flags, false);
+		}
+	}
+}
+
+/// Let everything through a broken shield, or make it block again.
+fn set_broken(state: &mut ShieldState, flags: &mut ColliderFlags, broken: bool) {
+	state.broken = broken;
+	flags.collision_groups = if broken { InteractionGroups::none() } else { state.groups };
 }
 
 pub fn extract_render_state(
 	game_state: Res<GameServer>,
 	query: Query<(Entity, &HP, &Player, &Transform, &ShieldID)>,
-	shields: Query<(Entity, &HP, &ShieldType, &Transform)>,
+	shields: Query<(Entity, &HP, &ShieldType, &ShieldState, &Bash, &Transform)>,
 	obj_query: Query<(Entity, &HP, &Shape, &Transform)>,
 	celestial_query: Query<(Entity, &HP, &CelestialBody, &Transform)>,
+	energies: Query<&Energy>,
 ) {
 	for (entity, _hp, _player, self_pos, _shield_id) in query.iter() {
-		// Collect players' positions.
-		let positions = query
-			.iter()
-			.filter_map(|(entity, hp, player, pos, shield_id)| {
-				if (self_pos.translation.x - pos.translation.x).abs() < VIEW_X
-					&& (self_pos.translation.y - pos.translation.y).abs() < VIEW_Y
-				{
-					Some((
-						entity.to_bits(),
-						PlayerView {
-							name: player.name.clone(),
-							pos: Position { x: pos.translation.x, y: pos.translation.y },
-							// TODO: this isn't used in rendering.
-							ori: {
-								let (axis, angle) = pos.rotation.to_axis_angle();
-								axis[2] * angle
-							},
-							shield_id: shield_id.entity.to_bits(),
-							hp: hp.val,
-						},
-					))
-				} else {
-					None
-				}
-			})
-			.collect();
+		// Players without a session, e.g. simulated ones, have nobody to send views to.
+		let session = match game_state.sessions.get(&entity) {
+			Some(session) => session,
+			None => continue,
+		};
+		let mut state = snapshot(
+			game_state.start_time.elapsed(),
+			Some(self_pos),
+			&query,
+			&shields,
+			&obj_query,
+			&celestial_query,
+		);
+		state.energy = energies.get(entity).ok().map(|energy| energy.val);
+		session.do_send(Push(ServerMessage::View(state)));
+	}
+}
 
-		let shield_info = shields
-			.iter()
-			.filter_map(|(entity, hp, _, pos)| {
-				if (self_pos.translation.x - pos.translation.x).abs() < VIEW_X
-					&& (self_pos.translation.y - pos.translation.y).abs() < VIEW_Y
-				{
-					Some((
-						entity.to_bits(),
-						ShieldView { pos: Position { x: pos.translation.x, y: pos.translation.y }, hp: hp.val },
-					))
-				} else {
-					None
-				}
-			})
-			.collect();
+/// Record the whole world to the replay at the configured interval.
+pub fn record_world(
+	game_state: Res<GameServer>,
+	map: Res<Map>,
+	recorder: Option<ResMut<Recorder>>,
+	query: Query<(Entity, &HP, &Player, &Transform, &ShieldID)>,
+	shields: Query<(Entity, &HP, &ShieldType, &ShieldState, &Bash, &Transform)>,
+	obj_query: Query<(Entity, &HP, &Shape, &Transform)>,
+	celestial_query: Query<(Entity, &HP, &CelestialBody, &Transform)>,
+) {
+	let mut recorder = match recorder {
+		Some(recorder) if recorder.wants_world() => recorder,
+		_ => return,
+	};
+	let mut state = snapshot(
+		game_state.start_time.elapsed(),
+		None,
+		&query,
+		&shields,
+		&obj_query,
+		&celestial_query,
+	);
+	state.self_pos = Position { x: map.width / 2.0, y: map.height / 2.0 };
+	recorder.record_world(state);
+}
 
-		// Collect positions of static objects.
-		let static_pos = obj_query
-			.iter()
-			.filter_map(|(entity, hp, _, pos)| {
-				if (self_pos.translation.x - pos.translation.x).abs() < VIEW_X
-					&& (self_pos.translation.y - pos.translation.y).abs() < VIEW_Y
-				{
-					Some((
-						entity.to_bits(),
-						StaticView { pos: Position { x: pos.translation.x, y: pos.translation.y }, hp: hp.val },
-					))
-				} else {
-					None
-				}
-			})
-			.collect();
+/// Collect objects within the view centered at `self_pos`,
+/// or all objects if there is no `self_pos`.
+fn snapshot(
+	time: Duration,
+	self_pos: Option<&Transform>,
+	query: &Query<(Entity, &HP, &Player, &Transform, &ShieldID)>,
+	shields: &Query<(Entity, &HP, &ShieldType, &ShieldState, &Bash, &Transform)>,
+	obj_query: &Query<(Entity, &HP, &Shape, &Transform)>,
+	celestial_query: &Query<(Entity, &HP, &CelestialBody, &Transform)>,
+) -> ViewSnapshot {
+	let in_view = |pos: &Transform| match self_pos {
+		Some(self_pos) => {
+			(self_pos.translation.x - pos.translation.x).abs() < VIEW_X
+				&& (self_pos.translation.y - pos.translation.y).abs() < VIEW_Y
+		}
+		None => true,
+	};
 
-		// Collect celestial positions.
-		let celestial_pos = celestial_query
-			.iter()
-			.map(|(entity, hp, _, pos)| {
-				(
+	// Collect players' positions.
+	let positions = query
+		.iter()
+		.filter_map(|(entity, hp, player, pos, shield_id)| {
+			if in_view(pos) {
+				Some((
 					entity.to_bits(),
-					CelestialView { pos: Position { x: pos.translation.x, y: pos.translation.y }, hp: hp.val },
-				)
-			})
-			.collect();
+					PlayerView {
+						name: player.name.clone(),
+						pos: Position { x: pos.translation.x, y: pos.translation.y },
+						// TODO: this isn't used in rendering.
+						ori: {
+							let (axis, angle) = pos.rotation.to_axis_angle();
+							axis[2] * angle
+						},
+						shield_id: shield_id.entity.to_bits(),
+						hp: hp.val,
+						team: player.team,
+					},
+				))
+			} else {
+				None
+			}
+		})
+		.collect();
+
+	let shield_info = shields
+		.iter()
+		.filter_map(|(entity, hp, shield_type, state, bash, pos)| {
+			if in_view(pos) {
+				let (axis, angle) = pos.rotation.to_axis_angle();
+				Some((
+					entity.to_bits(),
+					ShieldView {
+						pos: Position { x: pos.translation.x, y: pos.translation.y },
+						rot: axis[2] * angle,
+						shield_type: *shield_type,
+						hp: hp.val,
+						broken: state.broken,
+						bash: bash.phase,
+					},
+				))
+			} else {
+				None
+			}
+		})
+		.collect();
+
+	// Collect positions of static objects.
+	let static_pos = obj_query
+		.iter()
+		.filter_map(|(entity, hp, shape, pos)| {
+			if in_view(pos) {
+				let (axis, angle) = pos.rotation.to_axis_angle();
+				Some((
+					entity.to_bits(),
+					StaticView {
+						pos: Position { x: pos.translation.x, y: pos.translation.y },
+						rot: axis[2] * angle,
+						shape: shape.clone(),
+						hp: hp.val,
+					},
+				))
+			} else {
+				None
+			}
+		})
+		.collect();
+
+	// Collect celestial positions.
+	let celestial_pos = celestial_query
+		.iter()
+		.map(|(entity, hp, cb, pos)| {
+			(
+				entity.to_bits(),
+				CelestialView {
+					pos: Position { x: pos.translation.x, y: pos.translation.y },
+					radius: cb.radius,
+					form: cb.form,
+					hp: hp.val,
+				},
+			)
+		})
+		.collect();
+
+	// Collect self position.
+	let self_pos = match self_pos {
+		Some(self_pos) => Position { x: self_pos.translation.x, y: self_pos.translation.y },
+		None => Position::default(),
+	};
+
+	ViewSnapshot {
+		time,
+		self_pos,
+		players: positions,
+		shield_info,
+		static_pos,
+		celestial_pos,
+		energy: None,
+	}
+}
 
-		// Collect self position.
-		let self_pos = Position { x: self_pos.translation.x, y: self_pos.translation.y };
+/// Move a player and its shield to (`x`, `y`) with full HP and no velocity.
+pub struct Respawn {
+	pub(crate) player: Entity,
+	pub(crate) x: f32,
+	pub(crate) y: f32,
+}
 
-		let state = ViewSnapshot {
-			time: game_state.start_time.elapsed(),
-			self_pos,
-			players: positions,
-			shield_info,
-			static_pos,
-			celestial_pos,
+impl Command for Respawn {
+	fn write(self: Box<Self>, world: &mut World) {
+		// The player may have left in the meantime.
+		let shield = match world.get::<ShieldID>(self.player) {
+			Some(shield_id) => shield_id.entity,
+			None => return,
 		};
-		game_state
-			.sessions
-			.get(&entity)
-			.expect("Left player still alive")
-			.do_send(View(state.clone()));
+		let positions = [(self.player, self.x, self.y), (shield, self.x + 40.0, self.y)];
+		for (entity, x, y) in positions.iter().copied() {
+			world.get_mut::<HP>(entity).expect("No component found.").val = 100;
+			*world.get_mut::<RigidBodyPosition>(entity).expect("No component found.") =
+				Vec2::new(x, y).into();
+			*world.get_mut::<RigidBodyVelocity>(entity).expect("No component found.") =
+				RigidBodyVelocity::default();
+		}
+
+		world.get_mut::<Energy>(self.player).expect("No component found.").val = MAX_ENERGY;
+
+		let mut shield = world.entity_mut(shield);
+		let mut state = shield.get_mut::<ShieldState>().expect("No component found.");
+		state.last_hit = f64::NEG_INFINITY;
+		state.regen = 0.0;
+		let broken = std::mem::replace(&mut state.broken, false);
+		let groups = state.groups;
+		if broken {
+			let mut flags = shield.get_mut::<ColliderFlags>().expect("No component found.");
+			flags.collision_groups = groups;
+		}
+	}
+}
+
+/// Despawn the body and shield of a player spawned in the same tick,
+/// which are not in the physics world yet.
+struct Unspawn {
+	player: Entity,
+}
+
+impl Command for Unspawn {
+	fn write(self: Box<Self>, world: &mut World) {
+		let shield = world.get::<ShieldID>(self.player).expect("No component found.").entity;
+		world.despawn(shield);
+		world.despawn(self.player);
+	}
+}
+
+/// Apply `impulse` to a rigid body that was hit by a bash.
+#[derive(Clone)]
+pub struct Knockback {
+	pub(crate) entity: Entity,
+	pub(crate) impulse: Vector2<f32>,
+}
+
+impl Command for Knockback {
+	fn write(self: Box<Self>, world: &mut World) {
+		// The entity may have been despawned earlier in the tick.
+		let mprops = match world.get::<RigidBodyMassProps>(self.entity) {
+			Some(mprops) => mprops.clone(),
+			None => return,
+		};
+		let mut velocity =
+			world.get_mut::<RigidBodyVelocity>(self.entity).expect("No component found.");
+		velocity.apply_impulse(&mprops, self.impulse);
 	}
 }
 
@@ -512,14 +1219,29 @@ pub struct ChangeMovement {
 	pub(crate) state: PlayerState,
 }
 
+impl ChangeMovement {
+	pub fn new(player: Entity, state: PlayerState) -> Self {
+		ChangeMovement { player, state }
+	}
+}
+
 impl Command for ChangeMovement {
 	fn write(self: Box<Self>, world: &mut World) {
 		let (fy, fx) = self.state.dir.map_or((0.0, 0.0), |dir| dir.sin_cos());
-		let mut thrust = world.get_mut::<Thrust>(self.player).expect("No component found.");
-		thrust.x = fx * 20000.0;
-		thrust.y = fy * 20000.0;
+		let force = world.get_resource::<Config>().expect("No config found.").tuning.thrust;
+		// The player may have left earlier in the tick.
+		let mut thrust = match world.get_mut::<Thrust>(self.player) {
+			Some(thrust) => thrust,
+			None => return,
+		};
+		thrust.x = fx * force;
+		thrust.y = fy * force;
 		let mut ori = world.get_mut::<Ori>(self.player).expect("No component found.");
 		ori.deg = self.state.ori;
 		ori.push = self.state.push_shield;
+		world.get_mut::<Energy>(self.player).expect("No component found.").boosting =
+			self.state.boost;
+		let shield = world.get::<ShieldID>(self.player).expect("No component found.").entity;
+		world.get_mut::<Bash>(shield).expect("No component found.").requested = self.state.bash;
 	}
 }
@@ -0,0 +1,100 @@
+mod headless;
+
+use bevy::prelude::*;
+use bevy_rapier2d::rapier::dynamics::{RigidBodyMassProps, RigidBodyPosition};
+use bevy_rapier2d::rapier::math::Isometry;
+
+use game_server::component::{Attackers, CelestialBody, CelestialForm, Fragment, Score, Shape};
+use game_server::config::Config;
+use game_server::server::GameClock;
+
+use headless::{scenario_config, Headless};
+
+/// Speed of players sent into the black hole.
+const FALL_SPEED: f32 = 1000.0;
+/// Ticks for the shields of spawned players to swing into place.
+const SETTLE_TICKS: usize = 30;
+
+/// Nothing but a black hole at (5000, 5000), light enough for the mass it swallows to show.
+const BLACK_HOLE: &str = r#"
+[[bodies]]
+form = "black_hole"
+x = 5000.0
+y = 5000.0
+radius = 150.0
+mass = 1000.0
+fixed = true
+"#;
+
+fn black_hole(world: &mut World) -> Entity {
+	let mut bodies = world.query::<(Entity, &CelestialBody)>();
+	let (hole, _) = bodies.iter(world).find(|(_, cb)| cb.form == CelestialForm::BlackHole).unwrap();
+	hole
+}
+
+fn mass(game: &Headless, entity: Entity) -> f32 {
+	1.0 / game.get::<RigidBodyMassProps>(entity).local_mprops.inv_mass
+}
+
+#[test]
+fn players_crossing_the_event_horizon_die_and_feed_the_hole() {
+	let config =
+		scenario_config("players_crossing_the_event_horizon_die_and_feed_the_hole", BLACK_HOLE);
+	let mut game = Headless::new(config);
+	let hole = black_hole(game.world());
+	let alice = game.spawn_player("alice", 5400.0, 5000.0);
+	let bob = game.spawn_player("bob", 1000.0, 1000.0);
+	game.tick(SETTLE_TICKS);
+	let hole_mass = game.get::<CelestialBody>(hole).mass;
+
+	game.launch(alice, 5400.0, 5000.0, Vec2::new(-FALL_SPEED, 0.0));
+	game.tick_until(60, |world| world.get::<Score>(alice).unwrap().deaths == 1);
+
+	// The hole gets the mass of Alice but not of the shield, and nobody gets the kill.
+	let gained = game.get::<CelestialBody>(hole).mass - hole_mass;
+	assert!((gained - mass(&game, alice)).abs() < 1e-3 * gained);
+	let bob = game.get::<Score>(bob);
+	assert_eq!((bob.kills, bob.assists), (0, 0));
+}
+
+#[test]
+fn players_pushed_into_black_holes_are_killed_by_the_pusher() {
+	let config =
+		scenario_config("players_pushed_into_black_holes_are_killed_by_the_pusher", BLACK_HOLE);
+	let mut game = Headless::new(config);
+	let alice = game.spawn_player("alice", 5400.0, 5000.0);
+	let bob = game.spawn_player("bob", 1000.0, 1000.0);
+	game.tick(SETTLE_TICKS);
+
+	// Bob has just hit Alice towards the hole.
+	let now = game.get_resource::<GameClock>().now();
+	game.world().get_mut::<Attackers>(alice).unwrap().hits.push((bob, now));
+	game.launch(alice, 5400.0, 5000.0, Vec2::new(-FALL_SPEED, 0.0));
+	game.tick_until(60, |world| world.get::<Score>(alice).unwrap().deaths == 1);
+
+	let bob = game.get::<Score>(bob);
+	assert_eq!((bob.kills, bob.assists), (1, 0));
+}
+
+#[test]
+fn debris_crossing_the_event_horizon_vanishes() {
+	let config = Config {
+		debris_count: 1,
+		..scenario_config("debris_crossing_the_event_horizon_vanishes", BLACK_HOLE)
+	};
+	let mut game = Headless::new(config);
+	game.tick(1);
+	let hole = black_hole(game.world());
+	let world = game.world();
+	let mut debris = world.query_filtered::<Entity, (With<Shape>, Without<Fragment>)>();
+	let piece = debris.iter(world).next().unwrap();
+	let (hole_mass, piece_mass) = (game.get::<CelestialBody>(hole).mass, mass(&game, piece));
+
+	let mut position = game.world().get_mut::<RigidBodyPosition>(piece).unwrap();
+	position.position = Isometry::translation(5000.0, 5000.0);
+	position.next_position = position.position;
+	game.tick_until(10, |world| world.get_entity(piece).is_none());
+
+	let gained = game.get::<CelestialBody>(hole).mass - hole_mass;
+	assert!((gained - piece_mass).abs() < 1e-3 * gained);
+}
@@ -0,0 +1,68 @@
+mod headless;
+
+use bevy::prelude::*;
+
+use game_server::ai::{Behavior, Bot};
+use game_server::component::HP;
+use game_server::config::Config;
+
+use headless::{empty_config, Headless};
+
+fn bots(world: &mut World) -> Vec<Entity> {
+	world.query_filtered::<Entity, With<Bot>>().iter(world).collect()
+}
+
+#[test]
+fn bots_keep_the_arena_at_the_minimum_population() {
+	let config = Config {
+		min_population: 3,
+		..empty_config("bots_keep_the_arena_at_the_minimum_population")
+	};
+	let mut game = Headless::new(config);
+	game.tick(2);
+	assert_eq!(bots(game.world()).len(), 3);
+
+	game.world().get_resource_mut::<Config>().unwrap().min_population = 1;
+	game.tick(2);
+	assert_eq!(bots(game.world()).len(), 1);
+}
+
+#[test]
+fn bots_chase_ram_and_flee_from_players() {
+	let config =
+		Config { min_population: 1, ..empty_config("bots_chase_ram_and_flee_from_players") };
+	let mut game = Headless::new(config);
+	game.tick(1);
+	let bot = bots(game.world())[0];
+	let behavior = |game: &Headless| game.get::<Bot>(bot).behavior;
+	// Bots see where everyone was after the last step of the physics world,
+	// so they take a tick to notice players being moved.
+	game.launch(bot, 1000.0, 1000.0, Vec2::ZERO);
+	game.tick(2);
+	assert_eq!(behavior(&game), Behavior::Wander);
+
+	let player = game.spawn_player("alice", 2000.0, 1000.0);
+	game.tick(2);
+	assert_eq!(behavior(&game), Behavior::Chase);
+	let distance = |game: &Headless| {
+		(game.get::<Transform>(player).translation - game.get::<Transform>(bot).translation)
+			.length()
+	};
+	let start = distance(&game);
+	game.tick(60);
+	assert!(distance(&game) < start);
+
+	game.launch(player, 1100.0, 1000.0, Vec2::ZERO);
+	game.launch(bot, 1000.0, 1000.0, Vec2::ZERO);
+	game.tick(2);
+	assert_eq!(behavior(&game), Behavior::Ram);
+
+	game.world().get_mut::<HP>(bot).unwrap().val = 10;
+	game.launch(player, 1500.0, 1000.0, Vec2::ZERO);
+	game.launch(bot, 1000.0, 1000.0, Vec2::ZERO);
+	game.tick(2);
+	assert_eq!(behavior(&game), Behavior::Flee);
+	let start = distance(&game);
+	game.tick(60);
+	assert!(distance(&game) > start);
+}
@@ -0,0 +1,111 @@
+mod headless;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{ColliderFlags, InteractionGroups};
+
+use game_server::component::{Attackers, Score, ShieldState, HP};
+use game_server::server::GameClock;
+
+use headless::{empty_config, Headless};
+
+/// Speed of players rammed into others, fast enough to deal damage.
+const RAM_SPEED: f32 = 1500.0;
+/// Ticks for the shields of spawned players to swing into place.
+const SETTLE_TICKS: usize = 30;
+
+#[test]
+fn kills_through_shields_credit_the_assistants() {
+	let mut game = Headless::new(empty_config("kills_through_shields_credit_the_assistants"));
+	let victim = game.spawn_player("victim", 1000.0, 1000.0);
+	let assistant = game.spawn_player("assistant", 1300.0, 1000.0);
+	let killer = game.spawn_player("killer", 3000.0, 3000.0);
+	game.tick(SETTLE_TICKS);
+
+	// The assistant runs into the shield of the victim, on its right.
+	game.launch(assistant, 1300.0, 1000.0, Vec2::new(-RAM_SPEED, 0.0));
+	game.tick_until(60, |world| !world.get::<Attackers>(victim).unwrap().hits.is_empty());
+	assert_eq!(game.get::<Attackers>(victim).hits[0].0, assistant);
+
+	// The killer finishes the victim off from above, away from its shield.
+	game.launch(assistant, 3000.0, 1000.0, Vec2::ZERO);
+	game.launch(victim, 1000.0, 1000.0, Vec2::ZERO);
+	game.launch(killer, 1000.0, 1300.0, Vec2::new(0.0, -RAM_SPEED));
+	game.world().get_mut::<HP>(victim).unwrap().val = 1;
+	game.tick_until(60, |world| world.get::<Score>(victim).unwrap().deaths == 1);
+
+	let (killer, assistant) = (game.get::<Score>(killer), game.get::<Score>(assistant));
+	assert_eq!((killer.kills, killer.assists), (1, 0));
+	assert_eq!((assistant.kills, assistant.assists), (0, 1));
+	assert!(killer.score > assistant.score && assistant.score > 0);
+}
+
+#[test]
+fn hits_long_ago_do_not_count_towards_kills() {
+	let mut game = Headless::new(empty_config("hits_long_ago_do_not_count_towards_kills"));
+	let victim = game.spawn_player("victim", 1000.0, 1000.0);
+	let attacker = game.spawn_player("attacker", 1000.0, 1300.0);
+	game.tick(SETTLE_TICKS);
+
+	game.launch(attacker, 1000.0, 1300.0, Vec2::new(0.0, -RAM_SPEED));
+	game.tick_until(60, |world| !world.get::<Attackers>(victim).unwrap().hits.is_empty());
+	game.launch(attacker, 3000.0, 3000.0, Vec2::ZERO);
+	// The hit is out of the assist window by the time the victim dies on its own.
+	game.tick(11 * 63);
+	game.world().get_mut::<HP>(victim).unwrap().val = 0;
+	game.tick(1);
+
+	assert_eq!(game.get::<Score>(victim).deaths, 1);
+	let attacker = game.get::<Score>(attacker);
+	assert_eq!((attacker.kills, attacker.assists), (0, 0));
+}
+
+#[test]
+fn shields_take_hits_for_their_players() {
+	let mut game = Headless::new(empty_config("shields_take_hits_for_their_players"));
+	let victim = game.spawn_player("victim", 1000.0, 1000.0);
+	let attacker = game.spawn_player("attacker", 1300.0, 1000.0);
+	game.tick(SETTLE_TICKS);
+	let shield = game.shield(victim);
+
+	game.launch(attacker, 1300.0, 1000.0, Vec2::new(-RAM_SPEED, 0.0));
+	game.tick_until(60, |world| world.get::<HP>(shield).unwrap().val < 100);
+	assert_eq!(game.get::<HP>(victim).val, 100);
+	assert!(!game.get::<ShieldState>(shield).broken);
+}
+
+#[test]
+fn broken_shields_pass_the_rest_of_the_damage_on() {
+	let mut game = Headless::new(empty_config("broken_shields_pass_the_rest_of_the_damage_on"));
+	let victim = game.spawn_player("victim", 1000.0, 1000.0);
+	let attacker = game.spawn_player("attacker", 1300.0, 1000.0);
+	game.tick(SETTLE_TICKS);
+	let shield = game.shield(victim);
+	game.world().get_mut::<HP>(shield).unwrap().val = 1;
+
+	game.launch(attacker, 1300.0, 1000.0, Vec2::new(-RAM_SPEED, 0.0));
+	game.tick_until(60, |world| world.get::<ShieldState>(shield).unwrap().broken);
+	assert_eq!(game.get::<HP>(shield).val, 0);
+	assert!(game.get::<HP>(victim).val < 100);
+}
+
+#[test]
+fn broken_shields_regenerate_out_of_combat() {
+	let mut game = Headless::new(empty_config("broken_shields_regenerate_out_of_combat"));
+	let victim = game.spawn_player("victim", 1000.0, 1000.0);
+	game.tick(SETTLE_TICKS);
+	let shield = game.shield(victim);
+	let now = game.world().get_resource::<GameClock>().unwrap().now();
+	game.world().get_mut::<ShieldState>(shield).unwrap().last_hit = now;
+	game.world().get_mut::<HP>(shield).unwrap().val = 0;
+	game.tick(1);
+	assert!(game.get::<ShieldState>(shield).broken);
+	assert_eq!(game.get::<ColliderFlags>(shield).collision_groups, InteractionGroups::none());
+
+	// Regeneration starts 3 seconds after the last hit, at 20 HP per second.
+	game.tick(4 * 63);
+	assert!(game.get::<HP>(shield).val > 0);
+	assert!(game.get::<ShieldState>(shield).broken);
+	game.tick_until(6 * 63, |world| !world.get::<ShieldState>(shield).unwrap().broken);
+	assert_eq!(game.get::<HP>(shield).val, 100);
+	assert_ne!(game.get::<ColliderFlags>(shield).collision_groups, InteractionGroups::none());
+}
@@ -0,0 +1,152 @@
+//! Runs the whole server on a free port and talks to it through native websocket clients.
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use actix_codec::Framed;
+use actix_web::dev::Server;
+use actix_web::rt::time::timeout;
+use actix_web::web::Bytes;
+use awc::ws::{Codec, Frame, Message};
+use awc::BoxedSocket;
+use futures::{SinkExt, StreamExt};
+
+use game_shared::{JoinInfo, Operation, PlayerState, PlayerView, ServerMessage, ViewSnapshot};
+
+use game_server::config::{Config, Tuning};
+use game_server::start_server;
+
+/// Time to wait for an expected message before failing.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_FRAME_SIZE: usize = 1 << 20;
+
+/// The default config without gravity and bots, which would move players around unpredictably.
+pub fn test_config() -> Config {
+	let tuning = Tuning { gravity_const: 0.0, ..Default::default() };
+	Config { min_population: 0, tuning, ..Default::default() }
+}
+
+pub struct TestServer {
+	_server: Server,
+	pub addr: SocketAddr,
+}
+
+impl TestServer {
+	pub fn start() -> Self {
+		TestServer::start_with(test_config())
+	}
+
+	pub fn start_with(config: Config) -> Self {
+		let (server, addr) = start_server(config, "127.0.0.1:0").expect("Cannot start server.");
+		TestServer { _server: server, addr }
+	}
+}
+
+pub struct TestClient {
+	framed: Framed<BoxedSocket, Codec>,
+}
+
+impl TestClient {
+	pub async fn connect(server: &TestServer) -> Self {
+		let (_, framed) = awc::Client::new()
+			.ws(format!("ws://{}/ws", server.addr))
+			.max_frame_size(MAX_FRAME_SIZE)
+			.connect()
+			.await
+			.expect("Cannot connect to server.");
+		TestClient { framed }
+	}
+
+	/// Connect and join the game as `name`.
+	pub async fn join(server: &TestServer, name: &str) -> Self {
+		let info = JoinInfo { name: name.to_string(), ..Default::default() };
+		TestClient::join_with(server, info).await
+	}
+
+	pub async fn join_with(server: &TestServer, info: JoinInfo) -> Self {
+		let mut client = TestClient::connect(server).await;
+		client.send(Operation::Join(info)).await;
+		client
+	}
+
+	pub async fn send(&mut self, operation: Operation) {
+		let bin = Bytes::from(operation.serialize());
+		self.framed.send(Message::Binary(bin)).await.expect("Cannot send operation.");
+	}
+
+	pub async fn update(&mut self, state: PlayerState) {
+		self.send(Operation::Update(state)).await;
+	}
+
+	/// Receive the next message, answering pings in the meantime.
+	pub async fn next_message(&mut self) -> ServerMessage {
+		loop {
+			let frame = timeout(TIMEOUT, self.framed.next())
+				.await
+				.expect("Timed out waiting for a message.")
+				.expect("Connection closed.")
+				.expect("Websocket protocol error.");
+			match frame {
+				Frame::Binary(bin) => return ServerMessage::deserialize(&bin),
+				Frame::Ping(msg) => {
+					self.framed.send(Message::Pong(msg)).await.expect("Cannot send pong.")
+				}
+				Frame::Close(reason) => panic!("Connection closed: {:?}", reason),
+				_ => (),
+			}
+		}
+	}
+
+	/// Receive messages until one satisfies `condition`.
+	pub async fn message_until(
+		&mut self,
+		mut condition: impl FnMut(&ServerMessage) -> bool,
+	) -> ServerMessage {
+		let deadline = Instant::now() + TIMEOUT;
+		loop {
+			let msg = self.next_message().await;
+			if condition(&msg) {
+				return msg;
+			}
+			assert!(Instant::now() < deadline, "Timed out waiting for a matching message.");
+		}
+	}
+
+	/// Receive views until one satisfies `condition`.
+	pub async fn view_until(
+		&mut self,
+		mut condition: impl FnMut(&ViewSnapshot) -> bool,
+	) -> ViewSnapshot {
+		let msg = self
+			.message_until(|msg| match msg {
+				ServerMessage::View(view) => condition(view),
+				_ => false,
+			})
+			.await;
+		match msg {
+			ServerMessage::View(view) => view,
+			_ => unreachable!(),
+		}
+	}
+
+	/// Receive leaderboards until one satisfies `condition` with the names on it.
+	pub async fn leaderboard_until(&mut self, mut condition: impl FnMut(&[&str]) -> bool) {
+		self.message_until(|msg| match msg {
+			ServerMessage::Leaderboard(scores) => {
+				let names: Vec<&str> = scores.iter().map(|score| score.name.as_str()).collect();
+				condition(&names)
+			}
+			_ => false,
+		})
+		.await;
+	}
+
+	pub async fn close(mut self) {
+		self.framed.send(Message::Close(None)).await.expect("Cannot close connection.");
+	}
+}
+
+/// The player named `name` in `view`.
+pub fn find_player<'a>(view: &'a ViewSnapshot, name: &str) -> Option<&'a PlayerView> {
+	view.players.iter().map(|(_, player)| player).find(|player| player.name == name)
+}
@@ -0,0 +1,94 @@
+use bevy::math::Vec2;
+
+use game_server::component::Material;
+use game_server::damage::{combined_inv_mass, contact_damage, Impactor};
+
+const DAMAGE_PER_IMPULSE: f32 = 0.01;
+
+fn impactor(material: Material, inv_mass: f32, vx: f32) -> Impactor {
+	Impactor { material, inv_mass, linvel: Vec2::new(vx, 0.0), dmg: 1 }
+}
+
+/// A shield pushed by a player of unit mass.
+fn shield(vx: f32, dmg: i32) -> Impactor {
+	let inv_mass = combined_inv_mass(60.0, 1.0);
+	Impactor { dmg, ..impactor(Material::Shield, inv_mass, vx) }
+}
+
+#[test]
+fn gentle_bumps_deal_no_damage() {
+	let a = impactor(Material::Body, 1.0, 50.0);
+	let b = impactor(Material::Body, 1.0, -50.0);
+	assert_eq!(contact_damage(&a, &b, DAMAGE_PER_IMPULSE), (0, 0));
+}
+
+#[test]
+fn damage_grows_with_speed() {
+	let still = impactor(Material::Body, 1.0, 0.0);
+	let (slow, _) =
+		contact_damage(&still, &impactor(Material::Body, 1.0, -500.0), DAMAGE_PER_IMPULSE);
+	let (fast, _) =
+		contact_damage(&still, &impactor(Material::Body, 1.0, -2000.0), DAMAGE_PER_IMPULSE);
+	assert!(slow > 0);
+	assert!(fast > 3 * slow);
+}
+
+#[test]
+fn collisions_of_equal_bodies_hurt_both_equally() {
+	let a = impactor(Material::Body, 1.0, 1000.0);
+	let b = impactor(Material::Body, 1.0, -1000.0);
+	let (dmg_a, dmg_b) = contact_damage(&a, &b, DAMAGE_PER_IMPULSE);
+	assert!(dmg_a > 0);
+	assert_eq!(dmg_a, dmg_b);
+}
+
+#[test]
+fn shields_hit_harder_than_bodies_and_take_less() {
+	let victim = impactor(Material::Body, 1.0, 0.0);
+	let (by_body, _) =
+		contact_damage(&victim, &impactor(Material::Body, 1.0, -1000.0), DAMAGE_PER_IMPULSE);
+	let (by_shield, to_shield) = contact_damage(&victim, &shield(-1000.0, 1), DAMAGE_PER_IMPULSE);
+	assert!(by_shield > 2 * by_body);
+	assert!(to_shield < by_shield);
+
+	// Spikes multiply the damage of the shield.
+	let (by_spike, _) = contact_damage(&victim, &shield(-1000.0, 3), DAMAGE_PER_IMPULSE);
+	assert!(by_spike >= 3 * by_shield - 1);
+}
+
+#[test]
+fn planets_crush_players_without_damage() {
+	let player = impactor(Material::Body, 1.0, 500.0);
+	let planet = impactor(Material::Planet, 1e-7, 0.0);
+	let (to_player, to_planet) = contact_damage(&player, &planet, DAMAGE_PER_IMPULSE);
+	assert!(to_player > 0);
+	assert_eq!(to_planet, 0);
+}
+
+#[test]
+fn big_impacts_chip_planets() {
+	let player = impactor(Material::Body, 1.0, 1500.0);
+	let planet = impactor(Material::Planet, 1e-7, 0.0);
+	let (to_player, to_planet) = contact_damage(&player, &planet, DAMAGE_PER_IMPULSE);
+	assert!(to_planet > 0);
+	assert!(to_planet < to_player);
+}
+
+#[test]
+fn boundaries_only_hurt_what_runs_into_them() {
+	let debris = impactor(Material::Debris, 1.0, 300.0);
+	let boundary = impactor(Material::Boundary, 0.0, 0.0);
+	let (to_debris, to_boundary) = contact_damage(&debris, &boundary, DAMAGE_PER_IMPULSE);
+	assert!(to_debris > 0);
+	assert_eq!(to_boundary, 0);
+
+	// Nothing happens between objects that can't move.
+	let wall = impactor(Material::Debris, 0.0, 0.0);
+	assert_eq!(contact_damage(&wall, &boundary, DAMAGE_PER_IMPULSE), (0, 0));
+}
+
+#[test]
+fn shields_move_with_the_mass_of_their_player() {
+	assert!((combined_inv_mass(60.0, 1.0) - 60.0 / 61.0).abs() < 1e-6);
+	assert_eq!(combined_inv_mass(0.0, 1.0), 0.0);
+}
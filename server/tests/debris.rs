@@ -0,0 +1,79 @@
+mod headless;
+
+use bevy::prelude::*;
+
+use game_server::component::{Fragment, Shape, HP};
+use game_server::config::Config;
+use game_shared::{VIEW_X, VIEW_Y};
+
+use headless::{empty_config, Headless};
+
+fn debris(world: &mut World) -> Vec<Entity> {
+	world.query_filtered::<Entity, (With<Shape>, Without<Fragment>)>().iter(world).collect()
+}
+
+fn fragments(world: &mut World) -> Vec<Entity> {
+	world.query_filtered::<Entity, With<Fragment>>().iter(world).collect()
+}
+
+#[test]
+fn debris_shatters_into_fragments_that_expire() {
+	let config = Config {
+		debris_count: 1,
+		fragment_secs: 1.0,
+		..empty_config("debris_shatters_into_fragments_that_expire")
+	};
+	let mut game = Headless::new(config);
+	game.tick(1);
+	let piece = debris(game.world())[0];
+
+	game.world().get_mut::<HP>(piece).unwrap().val = 0;
+	game.tick(1);
+	assert!(game.world().get_entity(piece).is_none());
+	assert!(!fragments(game.world()).is_empty());
+	// Another piece takes the place of the shattered one once it is gone.
+	game.tick(1);
+	assert_eq!(debris(game.world()).len(), 1);
+
+	game.tick(58);
+	assert!(!fragments(game.world()).is_empty());
+	game.tick(5);
+	assert!(fragments(game.world()).is_empty());
+}
+
+#[test]
+fn fragments_do_not_shatter_any_further() {
+	let config = Config { debris_count: 1, ..empty_config("fragments_do_not_shatter_any_further") };
+	let mut game = Headless::new(config);
+	game.tick(1);
+	let piece = debris(game.world())[0];
+	game.world().get_mut::<HP>(piece).unwrap().val = 0;
+	game.tick(1);
+
+	let fragments_before = fragments(game.world());
+	game.world().get_mut::<HP>(fragments_before[0]).unwrap().val = 0;
+	game.tick(1);
+	let fragments_after = fragments(game.world());
+	assert_eq!(fragments_after.len(), fragments_before.len() - 1);
+	assert!(fragments_after.iter().all(|fragment| fragments_before.contains(fragment)));
+}
+
+#[test]
+fn debris_respawns_out_of_sight_of_players() {
+	let config =
+		Config { debris_count: 5, ..empty_config("debris_respawns_out_of_sight_of_players") };
+	let mut game = Headless::new(config);
+	game.spawn_player("alice", 2000.0, 2000.0);
+	game.tick(1);
+	let old_debris = debris(game.world());
+	for piece in old_debris.iter() {
+		game.world().get_mut::<HP>(*piece).unwrap().val = 0;
+	}
+
+	game.tick_until(60, |world| debris(world).len() == 5);
+	for piece in debris(game.world()) {
+		assert!(!old_debris.contains(&piece));
+		let pos = game.get::<Transform>(piece).translation;
+		assert!((pos.x - 2000.0).abs() >= VIEW_X || (pos.y - 2000.0).abs() >= VIEW_Y);
+	}
+}
@@ -0,0 +1,40 @@
+mod headless;
+
+use bevy::prelude::*;
+
+use game_server::component::{Score, HP};
+use game_server::config::Config;
+
+use headless::Headless;
+
+const TICKS: usize = 300;
+
+/// Every entity with its position, HP and score after `TICKS` ticks of a world seeded with `seed`.
+fn run(seed: u64) -> Vec<(u64, Vec3, Option<i32>, Option<i32>)> {
+	let mut game = Headless::new(Config { seed: Some(seed), ..Default::default() });
+	// Players rush into the debris around the center, so that everything collides and takes damage.
+	for (i, (x, y)) in [(3500.0, 5000.0), (6500.0, 5000.0), (5000.0, 3500.0)].iter().enumerate() {
+		let player = game.spawn_player(&format!("player {}", i), *x, *y);
+		let velocity = (Vec2::new(5000.0, 5000.0) - Vec2::new(*x, *y)).normalize() * 500.0;
+		game.launch(player, *x, *y, velocity);
+	}
+	game.tick(TICKS);
+
+	let world = game.world();
+	let mut query = world.query::<(Entity, &Transform, Option<&HP>, Option<&Score>)>();
+	let mut state: Vec<_> = query
+		.iter(world)
+		.map(|(entity, transform, hp, score)| {
+			(entity.to_bits(), transform.translation, hp.map(|hp| hp.val), score.map(|s| s.score))
+		})
+		.collect();
+	state.sort_by_key(|(entity, ..)| *entity);
+	state
+}
+
+#[test]
+fn the_same_seed_yields_the_same_world() {
+	let first = run(7);
+	assert!(!first.is_empty());
+	assert_eq!(first, run(7));
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use std::f32::consts::PI;
+
+use game_shared::shield::{BashPhase, ShieldType};
+use game_shared::{
+	JoinInfo, PlayerState, ServerMessage, ViewSnapshot, MAP_HEIGHT, MAP_WIDTH, MAX_ENERGY,
+};
+
+use game_server::config::Config;
+
+use common::{find_player, test_config, TestClient, TestServer};
+
+#[actix_rt::test]
+async fn players_join_and_receive_snapshots() {
+	let server = TestServer::start();
+	let mut alice = TestClient::join(&server, "alice").await;
+	let mut bob = TestClient::join(&server, "bob").await;
+
+	for (client, name) in vec![(&mut alice, "alice"), (&mut bob, "bob")] {
+		let view = client.view_until(|view| find_player(view, name).is_some()).await;
+		let player = find_player(&view, name).unwrap();
+		assert_eq!(player.hp, 100);
+		assert_eq!((player.pos.x, player.pos.y), (view.self_pos.x, view.self_pos.y));
+		assert!(view.shield_info.iter().any(|(id, _)| *id == player.shield_id));
+		client.leaderboard_until(|names| names.contains(&"alice") && names.contains(&"bob")).await;
+	}
+}
+
+#[actix_rt::test]
+async fn players_receive_the_map_when_joining() {
+	let server = TestServer::start();
+	let mut alice = TestClient::join(&server, "alice").await;
+	let msg = alice.message_until(|msg| matches!(msg, ServerMessage::Map(_))).await;
+	if let ServerMessage::Map(map) = msg {
+		assert_eq!((map.width, map.height), (MAP_WIDTH, MAP_HEIGHT));
+		assert_eq!(map.boundaries.len(), 1);
+	}
+}
+
+#[actix_rt::test]
+async fn players_move_in_their_direction() {
+	let server = TestServer::start();
+	let mut alice = TestClient::join(&server, "alice").await;
+	let start = alice.view_until(|view| find_player(view, "alice").is_some()).await.self_pos;
+
+	let state =
+		PlayerState { dir: Some(0.0), ori: 0.0, push_shield: false, boost: false, bash: false };
+	alice.update(state.clone()).await;
+	alice.view_until(|view| view.self_pos.x > start.x + 100.0).await;
+	alice.update(PlayerState { dir: Some(PI / 2.0), ..state }).await;
+	alice.view_until(|view| view.self_pos.y > start.y + 100.0).await;
+}
+
+#[actix_rt::test]
+async fn boosts_spend_energy() {
+	let server = TestServer::start();
+	let mut alice = TestClient::join(&server, "alice").await;
+	alice.view_until(|view| view.energy == Some(MAX_ENERGY)).await;
+
+	let state =
+		PlayerState { dir: Some(0.0), ori: 0.0, push_shield: false, boost: true, bash: false };
+	alice.update(state).await;
+	alice.view_until(|view| view.energy.map_or(false, |energy| energy < MAX_ENERGY)).await;
+}
+
+#[actix_rt::test]
+async fn bashes_cool_down() {
+	let server = TestServer::start();
+	let mut alice = TestClient::join(&server, "alice").await;
+	let bash = |view: &ViewSnapshot| {
+		let shield_id = find_player(view, "alice")?.shield_id;
+		view.shield_info.iter().find(|(id, _)| *id == shield_id).map(|(_, shield)| shield.bash)
+	};
+	alice.view_until(|view| bash(view) == Some(BashPhase::Ready)).await;
+
+	let state = PlayerState { dir: None, ori: 0.0, push_shield: false, boost: false, bash: true };
+	alice.update(state).await;
+	alice.view_until(|view| bash(view) == Some(BashPhase::Active)).await;
+	alice.view_until(|view| bash(view) == Some(BashPhase::Cooldown)).await;
+}
+
+#[actix_rt::test]
+async fn collisions_deal_damage() {
+	let server = TestServer::start();
+	let mut alice = TestClient::join(&server, "alice").await;
+	// Updates sent before the player is spawned are dropped.
+	alice.view_until(|view| find_player(view, "alice").is_some()).await;
+
+	// Run into the left boundary of the map.
+	let state =
+		PlayerState { dir: Some(PI), ori: PI, push_shield: true, boost: false, bash: false };
+	alice.update(state).await;
+	alice
+		.view_until(|view| find_player(view, "alice").map_or(false, |player| player.hp < 100))
+		.await;
+}
+
+#[actix_rt::test]
+async fn leaving_players_are_cleaned_up() {
+	let server = TestServer::start();
+	let mut alice = TestClient::join(&server, "alice").await;
+	let bob = TestClient::join(&server, "bob").await;
+	alice.leaderboard_until(|names| names.contains(&"bob")).await;
+
+	bob.close().await;
+	alice.leaderboard_until(|names| !names.contains(&"bob")).await;
+	// The world keeps running without the body, shield and joint of the player.
+	let time = alice.view_until(|_| true).await.time;
+	alice.view_until(|view| view.time > time + std::time::Duration::from_secs(1)).await;
+}
+
+#[actix_rt::test]
+async fn players_wait_in_queue_when_the_arena_is_full() {
+	let server = TestServer::start_with(Config { max_players: Some(1), ..test_config() });
+	let mut alice = TestClient::join(&server, "alice").await;
+	alice.view_until(|view| find_player(view, "alice").is_some()).await;
+	let mut bob = TestClient::join(&server, "bob").await;
+	bob.message_until(|msg| matches!(msg, ServerMessage::Queued(1))).await;
+
+	alice.close().await;
+	bob.view_until(|view| find_player(view, "bob").is_some()).await;
+}
+
+#[actix_rt::test]
+async fn players_leaving_before_they_join_leave_nothing_behind() {
+	let server = TestServer::start_with(Config { max_players: Some(1), ..test_config() });
+	let mut alice = TestClient::join(&server, "alice").await;
+	alice.view_until(|view| find_player(view, "alice").is_some()).await;
+	let mut bob = TestClient::join(&server, "bob").await;
+	bob.message_until(|msg| matches!(msg, ServerMessage::Queued(1))).await;
+
+	// Bob's join is handled once there is room, after Bob has left.
+	bob.close().await;
+	alice.close().await;
+	let mut carol = TestClient::join(&server, "carol").await;
+	carol.view_until(|view| find_player(view, "carol").is_some()).await;
+	carol.leaderboard_until(|names| names == ["carol"]).await;
+}
+
+#[actix_rt::test]
+async fn players_are_split_into_teams() {
+	let server = TestServer::start_with(Config { teams: 2, ..test_config() });
+	let info = JoinInfo { name: "alice".to_string(), team: Some(1), ..Default::default() };
+	let mut alice = TestClient::join_with(&server, info).await;
+	let view = alice.view_until(|view| find_player(view, "alice").is_some()).await;
+	assert_eq!(find_player(&view, "alice").unwrap().team, Some(1));
+
+	// Joining players without a choice are put in the smaller team.
+	let mut bob = TestClient::join(&server, "bob").await;
+	let view = bob.view_until(|view| find_player(view, "bob").is_some()).await;
+	assert_eq!(find_player(&view, "bob").unwrap().team, Some(0));
+	bob.message_until(|msg| matches!(msg, ServerMessage::TeamScores(scores) if scores.len() == 2))
+		.await;
+}
+
+#[actix_rt::test]
+async fn players_join_with_the_chosen_shield() {
+	let server = TestServer::start();
+	let info =
+		JoinInfo { name: "alice".to_string(), shield: ShieldType::Spike, ..Default::default() };
+	let mut alice = TestClient::join_with(&server, info).await;
+	let view = alice.view_until(|view| find_player(view, "alice").is_some()).await;
+	let shield_id = find_player(&view, "alice").unwrap().shield_id;
+	let (_, shield) = view.shield_info.iter().find(|(id, _)| *id == shield_id).unwrap();
+	assert_eq!(shield.shield_type, ShieldType::Spike);
+}
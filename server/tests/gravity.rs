@@ -0,0 +1,148 @@
+use bevy::math::Vec2;
+use bevy_rapier2d::na::Vector2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use game_server::barnes_hut::QuadTree;
+use game_server::gravity::{acceleration, energy, leapfrog, Attractor, Orbiter};
+use game_server::scenario::Scenario;
+use game_shared::{MAP_HEIGHT, MAP_WIDTH};
+
+const GRAVITY_CONST: f32 = 20.0;
+
+#[test]
+fn bodies_fall_towards_attractors_by_the_inverse_square() {
+	let attractors = [Attractor { pos: Vec2::new(0.0, 0.0), mass: 1000.0 }];
+	let near = acceleration(Vec2::new(100.0, 0.0), &attractors, GRAVITY_CONST);
+	let far = acceleration(Vec2::new(200.0, 0.0), &attractors, GRAVITY_CONST);
+	assert!(near.x < 0.0 && near.y == 0.0);
+	assert!((near.x / far.x - 4.0).abs() < 1e-4);
+}
+
+#[test]
+fn pulls_of_attractors_add_up() {
+	let attractors = [
+		Attractor { pos: Vec2::new(-100.0, 0.0), mass: 1000.0 },
+		Attractor { pos: Vec2::new(100.0, 0.0), mass: 1000.0 },
+	];
+	// Equal pulls from both sides cancel out.
+	assert_eq!(acceleration(Vec2::new(0.0, 0.0), &attractors, GRAVITY_CONST), Vec2::ZERO);
+	let accel = acceleration(Vec2::new(0.0, 100.0), &attractors, GRAVITY_CONST);
+	assert!(accel.x.abs() < 1e-6 && accel.y < 0.0);
+}
+
+#[test]
+fn attractors_do_not_pull_themselves() {
+	let attractors = [Attractor { pos: Vec2::new(50.0, 50.0), mass: 1000.0 }];
+	assert_eq!(acceleration(Vec2::new(50.0, 50.0), &attractors, GRAVITY_CONST), Vec2::ZERO);
+}
+
+fn orbiters(scenario: &str) -> Vec<Orbiter> {
+	Scenario::load(scenario)
+		.bodies
+		.iter()
+		.map(|body| Orbiter {
+			pos: Vector2::new(body.x as f64, body.y as f64),
+			vel: Vector2::new(body.velocity[0] as f64, body.velocity[1] as f64),
+			mass: body.mass(0.0) as f64,
+			radius: body.radius as f64,
+			fixed: body.fixed,
+		})
+		.collect()
+}
+
+#[test]
+fn the_figure_eight_conserves_energy_and_returns() {
+	let mut bodies = orbiters("figure_eight");
+	let start = bodies.clone();
+	let initial = energy(&bodies, GRAVITY_CONST as f64);
+	// About ten periods of 14.1 seconds, at the rate the server steps orbits.
+	let dt = 0.004;
+	let period = 14.146;
+	let mut max_drift: f64 = 0.0;
+	for step in 1..=(10.0 * period / dt) as usize {
+		leapfrog(&mut bodies, GRAVITY_CONST as f64, dt);
+		max_drift = max_drift.max((energy(&bodies, GRAVITY_CONST as f64) / initial - 1.0).abs());
+		// The bodies stay near the center of the map instead of flying apart.
+		if step % 1000 == 0 {
+			assert!(bodies.iter().all(|body| (body.pos - start[1].pos).norm() < 1500.0));
+		}
+	}
+	assert!(max_drift < 1e-5, "the energy drifted by {}", max_drift);
+}
+
+#[test]
+fn fixed_bodies_pull_without_moving() {
+	let mut bodies = orbiters("sun");
+	let sun = bodies[0];
+	let radii: Vec<f64> = bodies.iter().map(|body| (body.pos - sun.pos).norm()).collect();
+	for _ in 0..10000 {
+		leapfrog(&mut bodies, GRAVITY_CONST as f64, 0.004);
+	}
+	assert_eq!(bodies[0], sun);
+	// The planets stay near their orbits, slightly disturbed by each other.
+	for (body, radius) in bodies.iter().zip(radii).skip(1) {
+		assert!(((body.pos - sun.pos).norm() / radius - 1.0).abs() < 0.1);
+	}
+}
+
+#[test]
+fn binary_planets_stay_apart() {
+	let mut bodies = orbiters("binary");
+	for _ in 0..50000 {
+		leapfrog(&mut bodies, GRAVITY_CONST as f64, 0.004);
+		assert!(((bodies[1].pos - bodies[0].pos).norm() / 1600.0 - 1.0).abs() < 1e-3);
+	}
+}
+
+fn random_point(rng: &mut StdRng) -> Vec2 {
+	Vec2::new(rng.gen_range(0.0..MAP_WIDTH), rng.gen_range(0.0..MAP_HEIGHT))
+}
+
+fn random_attractors(rng: &mut StdRng, count: usize) -> Vec<Attractor> {
+	(0..count)
+		.map(|_| Attractor { pos: random_point(rng), mass: rng.gen_range(1e5..1e7) })
+		.collect()
+}
+
+/// The relative errors of the pull of `tree` at random points against the exact pull.
+fn tree_errors(rng: &mut StdRng, attractors: &[Attractor], opening_angle: f32) -> Vec<f32> {
+	let tree = QuadTree::new(attractors);
+	(0..500)
+		.map(|_| {
+			let pos = random_point(rng);
+			let exact = acceleration(pos, attractors, GRAVITY_CONST);
+			let approx = tree.acceleration(pos, GRAVITY_CONST, opening_angle);
+			(approx - exact).length() / exact.length()
+		})
+		.collect()
+}
+
+#[test]
+fn quadtrees_without_an_opening_angle_are_exact() {
+	let mut rng = StdRng::seed_from_u64(0);
+	let attractors = random_attractors(&mut rng, 1000);
+	let max_error = tree_errors(&mut rng, &attractors, 0.0).into_iter().fold(0.0, f32::max);
+	assert!(max_error < 1e-3, "the pull is off by {}", max_error);
+}
+
+#[test]
+fn quadtrees_approximate_the_pull_of_many_attractors() {
+	let mut rng = StdRng::seed_from_u64(1);
+	for &count in [10, 100, 1000, 5000].iter() {
+		let attractors = random_attractors(&mut rng, count);
+		let errors = tree_errors(&mut rng, &attractors, 0.5);
+		let mean_error = errors.iter().sum::<f32>() / errors.len() as f32;
+		assert!(mean_error < 0.02, "the pull of {} is off by {} on average", count, mean_error);
+	}
+}
+
+#[test]
+fn quadtrees_handle_attractors_at_the_same_spot() {
+	let attractors = vec![Attractor { pos: Vec2::new(1.0, 1.0), mass: 5.0 }; 10];
+	let tree = QuadTree::new(&attractors);
+	let pos = Vec2::new(0.0, 1.0);
+	let exact = acceleration(pos, &attractors, GRAVITY_CONST);
+	assert!((tree.acceleration(pos, GRAVITY_CONST, 0.5) - exact).length() < 1e-3);
+	assert_eq!(QuadTree::new(&[]).acceleration(pos, GRAVITY_CONST, 0.5), Vec2::ZERO);
+}
@@ -0,0 +1,130 @@
+//! Runs the game without networking, tick by tick, with players placed by the tests.
+
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use bevy::ecs::component::Component;
+use bevy::ecs::system::CommandQueue;
+use bevy::prelude::*;
+use bevy_rapier2d::na::Vector2;
+use bevy_rapier2d::physics::RapierPhysicsPlugin;
+use bevy_rapier2d::prelude::NoUserData;
+use bevy_rapier2d::rapier::dynamics::{RigidBodyPosition, RigidBodyVelocity};
+use futures::channel::mpsc::UnboundedSender;
+
+use game_server::build_game;
+use game_server::component::{Player, ShieldID, ShieldType};
+use game_server::config::{Config, Tuning};
+use game_server::event::{EventListener, GameEvent};
+use game_server::map::Map;
+use game_server::server::GameRng;
+use game_server::system::spawn_player;
+
+pub struct Headless {
+	pub app: App,
+	// Nobody joins through the network, but the channel has to stay open for the game to poll it.
+	_sender: UnboundedSender<GameEvent>,
+}
+
+/// A config of an empty map without walls, celestial bodies, gravity, bots or debris,
+/// so that only what the test places moves. The map is written to a file named after `test`.
+pub fn empty_config(test: &str) -> Config {
+	let path = std::env::temp_dir().join(format!("{}.toml", test));
+	std::fs::write(&path, "").unwrap();
+	let tuning = Tuning { gravity_const: 0.0, ..Default::default() };
+	Config {
+		min_population: 0,
+		debris_count: 0,
+		map: path.to_str().unwrap().to_string(),
+		seed: Some(0),
+		tuning,
+		..Default::default()
+	}
+}
+
+/// `empty_config` with the celestial bodies of `scenario`, in a scenario file named after `test`.
+pub fn scenario_config(test: &str, scenario: &str) -> Config {
+	let path = std::env::temp_dir().join(format!("{}_scenario.toml", test));
+	std::fs::write(&path, scenario).unwrap();
+	Config { scenario: Some(path.to_str().unwrap().to_string()), ..empty_config(test) }
+}
+
+impl Headless {
+	pub fn new(config: Config) -> Self {
+		let (sender, receiver) = futures::channel::mpsc::unbounded();
+		let mut app = App::build();
+		app.add_plugins(MinimalPlugins).add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+		build_game(&mut app, config, EventListener(receiver));
+		let mut game = Headless { app: app.app, _sender: sender };
+		// Set the world up before the tests place anything in it.
+		game.tick(1);
+		game
+	}
+
+	pub fn world(&mut self) -> &mut World {
+		&mut self.app.world
+	}
+
+	pub fn tick(&mut self, ticks: usize) {
+		for _ in 0..ticks {
+			self.app.update();
+		}
+	}
+
+	/// Tick until `condition` holds, failing after `ticks` ticks.
+	pub fn tick_until(&mut self, ticks: usize, mut condition: impl FnMut(&mut World) -> bool) {
+		for _ in 0..ticks {
+			self.app.update();
+			if condition(self.world()) {
+				return;
+			}
+		}
+		panic!("The condition didn't hold within {} ticks.", ticks);
+	}
+
+	/// Spawn a player called `name` at (`x`, `y`) with its shield towards the positive x axis.
+	pub fn spawn_player(&mut self, name: &str, x: f32, y: f32) -> Entity {
+		let world = &mut self.app.world;
+		let config = world.get_resource::<Config>().unwrap().clone();
+		let map = world.get_resource::<Map>().unwrap().clone();
+		let mut rng = world.remove_resource::<GameRng>().unwrap();
+		let mut queue = CommandQueue::default();
+		let mut commands = Commands::new(&mut queue, world);
+		let player = Player { name: name.to_string(), team: None };
+		let player =
+			spawn_player(&mut commands, player, ShieldType::Circle, &mut rng.0, &config, &map);
+		queue.apply(world);
+		world.insert_resource(rng);
+		self.launch(player, x, y, Vec2::ZERO);
+		player
+	}
+
+	/// Move a player to (`x`, `y`) along with its shield, which stays where it is relative to
+	/// the player, and set both moving with `velocity`.
+	pub fn launch(&mut self, player: Entity, x: f32, y: f32, velocity: Vec2) {
+		let shield = self.shield(player);
+		let world = self.world();
+		let from = world.get::<RigidBodyPosition>(player).unwrap().position.translation.vector;
+		let offset = Vector2::new(x, y) - from;
+		for entity in [player, shield].iter().copied() {
+			let mut position = world.get_mut::<RigidBodyPosition>(entity).unwrap();
+			position.position.translation.vector += offset;
+			position.next_position = position.position;
+			let mut body_velocity = world.get_mut::<RigidBodyVelocity>(entity).unwrap();
+			body_velocity.linvel = Vector2::new(velocity.x, velocity.y);
+			body_velocity.angvel = 0.0;
+		}
+	}
+
+	pub fn shield(&self, player: Entity) -> Entity {
+		self.app.world.get::<ShieldID>(player).unwrap().entity
+	}
+
+	pub fn get<T: Component>(&self, entity: Entity) -> &T {
+		self.app.world.get::<T>(entity).unwrap()
+	}
+
+	pub fn get_resource<T: Component>(&self) -> &T {
+		self.app.world.get_resource::<T>().unwrap()
+	}
+}
@@ -0,0 +1,93 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use game_server::map::{Map, Region};
+use game_shared::shape::Shape;
+use game_shared::{MAP_HEIGHT, MAP_WIDTH};
+
+#[test]
+fn bundled_maps_keep_everything_within_their_walls() {
+	for name in Map::bundled() {
+		let map = Map::load(name);
+		let whole = Region { x: 0.0, y: 0.0, width: map.width, height: map.height };
+		assert!(!map.boundaries.is_empty(), "{} has no walls", name);
+		for boundary in map.boundaries.iter() {
+			assert!(boundary.points.len() >= 2);
+			assert!(boundary.points.iter().all(|&[x, y]| whole.contains(x, y)));
+		}
+		for region in map.spawns.iter().chain(map.debris_fields.iter()) {
+			assert!(whole.contains(region.x, region.y));
+			assert!(whole.contains(region.x + region.width, region.y + region.height));
+		}
+		for body in map.celestial_bodies(None).iter() {
+			assert!(body.x - body.radius > 0.0 && body.x + body.radius < map.width);
+			assert!(body.y - body.radius > 0.0 && body.y + body.radius < map.height);
+		}
+	}
+}
+
+#[test]
+fn the_arena_is_the_classic_square() {
+	let map = Map::load("arena");
+	assert_eq!((map.width, map.height), (MAP_WIDTH, MAP_HEIGHT));
+	let view = map.view();
+	assert_eq!(view.boundaries.len(), 1);
+	assert_eq!(view.boundaries[0].first(), view.boundaries[0].last());
+	assert_eq!(map.celestial_bodies(None).len(), 3);
+	// A scenario given in the config replaces the bodies of the map.
+	assert_eq!(map.celestial_bodies(Some("black_hole"))[0].radius, 150.0);
+}
+
+#[test]
+fn players_spawn_in_spawn_regions() {
+	let map = Map::load("asteroids");
+	let mut rng = StdRng::seed_from_u64(0);
+	for _ in 0..1000 {
+		let (x, y) = map.spawn_point(&mut rng);
+		assert!(map.spawns.iter().any(|region| region.contains(x, y)));
+		let (x, y) = map.debris_point(&mut rng);
+		assert!(map.debris_fields.iter().any(|region| region.contains(x, y)));
+	}
+}
+
+#[test]
+fn map_files_fill_in_defaults() {
+	let path = std::env::temp_dir().join("map_files_fill_in_defaults.toml");
+	let content = "[[obstacles]]\nshape = { rect = { half_width = 50.0, half_height = 20.0 } }\n\
+		x = 300.0\ny = 400.0\n";
+	std::fs::write(&path, content).unwrap();
+	let map = Map::load(path.to_str().unwrap());
+	assert_eq!((map.width, map.height), (MAP_WIDTH, MAP_HEIGHT));
+	assert!(map.boundaries.is_empty() && map.celestial_bodies(None).is_empty());
+	let obstacle = &map.obstacles[0];
+	assert_eq!(obstacle.shape, Shape::Rect { half_width: 50.0, half_height: 20.0 });
+	assert_eq!(obstacle.rot, 0.0);
+
+	// Without spawn regions, players spawn in the middle of the map.
+	let mut rng = StdRng::seed_from_u64(0);
+	let middle = Region { x: 4000.0, y: 4000.0, width: 2000.0, height: 2000.0 };
+	let (x, y) = map.spawn_point(&mut rng);
+	assert!(middle.contains(x, y));
+}
+
+#[test]
+fn obstacles_without_an_area_are_rejected() {
+	let shapes = [
+		"{ circle = { radius = 0.0 } }",
+		"{ rect = { half_width = 50.0, half_height = -20.0 } }",
+		"{ polygon = { points = [{ x = 0.0, y = 0.0 }, { x = 10.0, y = 10.0 }, { x = 20.0, y = 20.0 }] } }",
+		"{ capsule = { half_length = 50.0, radius = nan } }",
+	];
+	for (i, shape) in shapes.iter().enumerate() {
+		let path = std::env::temp_dir().join(format!("obstacles_without_an_area_{}.toml", i));
+		let content = format!(
+			"[[obstacles]]\nshape = {{ circle = {{ radius = 10.0 }} }}\nx = 0.0\ny = 0.0\n\
+			[[obstacles]]\nshape = {}\nx = 0.0\ny = 0.0\n",
+			shape
+		);
+		std::fs::write(&path, content).unwrap();
+		let panic = std::panic::catch_unwind(|| Map::load(path.to_str().unwrap())).unwrap_err();
+		let message = panic.downcast_ref::<String>().unwrap();
+		assert!(message.starts_with("Obstacle 1 of map"), "{}", message);
+	}
+}
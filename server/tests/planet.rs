@@ -0,0 +1,120 @@
+mod headless;
+
+use bevy::prelude::*;
+use bevy_rapier2d::na::Vector2;
+
+use game_server::component::{CelestialBody, CelestialForm, Orbit, Shape, HP};
+use game_server::planet::Impact;
+use game_shared::CELESTIAL_RADIUS;
+
+use headless::{scenario_config, Headless};
+
+const SHATTER_ENERGY: f32 = 50000.0;
+/// Pieces planets shatter into.
+const SHARDS: usize = 8;
+/// Speed of players rammed into planets, fast enough to chip them.
+const RAM_SPEED: f32 = 1500.0;
+/// Ticks for the shields of spawned players to swing into place.
+const SETTLE_TICKS: usize = 30;
+
+/// Two planets at (4000, 5000) and (4300, 5000) heading for each other at `speed` each.
+fn head_on(speed: f32) -> String {
+	let planet = |x: f32, vx: f32| {
+		format!("[[bodies]]\nx = {}\ny = 5000.0\nmass = 1000000.0\nvelocity = [{}, 0.0]\n", x, vx)
+	};
+	planet(4000.0, speed) + &planet(4300.0, -speed)
+}
+
+fn body(mass: f32, fixed: bool) -> CelestialBody {
+	CelestialBody { form: CelestialForm::Planet, radius: 100.0, mass, fixed }
+}
+
+fn orbit(x: f64, vx: f64) -> Orbit {
+	Orbit { pos: Vector2::new(x, 0.0), vel: Vector2::new(vx, 0.0) }
+}
+
+#[test]
+fn merging_conserves_mass_and_momentum() {
+	let (heavy, light) = (body(3e7, false), body(1e7, false));
+	let (orbit1, orbit2) = (orbit(0.0, 100.0), orbit(150.0, -100.0));
+	let impact = Impact { bodies: [(&heavy, &orbit1), (&light, &orbit2)] };
+	assert!(!impact.shatters(SHATTER_ENERGY));
+
+	let merged = impact.merged();
+	assert_eq!(merged.mass, Some(4e7));
+	assert!((merged.x - 37.5).abs() < 1e-3);
+	assert!((merged.velocity[0] - 50.0).abs() < 1e-3);
+	assert_eq!(merged.velocity[1], 0.0);
+	// The merged body covers the area of both.
+	assert!((merged.radius - 100.0 * 2f32.sqrt()).abs() < 1e-3);
+}
+
+#[test]
+fn fast_impacts_shatter_planets() {
+	let (a, b) = (body(1e7, false), body(1e7, false));
+	let (slow1, slow2) = (orbit(0.0, 200.0), orbit(150.0, -200.0));
+	assert!(!Impact { bodies: [(&a, &slow1), (&b, &slow2)] }.shatters(SHATTER_ENERGY));
+	let (fast1, fast2) = (orbit(0.0, 500.0), orbit(150.0, -500.0));
+	let impact = Impact { bodies: [(&a, &fast1), (&b, &fast2)] };
+	assert!(impact.energy() > SHATTER_ENERGY as f64);
+	assert!(impact.shatters(SHATTER_ENERGY));
+}
+
+#[test]
+fn fixed_bodies_absorb_whatever_hits_them() {
+	let (sun, planet) = (body(1e9, true), body(1e7, false));
+	let (orbit1, orbit2) = (orbit(0.0, 0.0), orbit(150.0, -5000.0));
+	let impact = Impact { bodies: [(&planet, &orbit2), (&sun, &orbit1)] };
+	assert!(!impact.shatters(SHATTER_ENERGY));
+
+	let merged = impact.merged();
+	assert!(merged.fixed);
+	assert_eq!((merged.x, merged.y), (0.0, 0.0));
+	assert_eq!(merged.velocity, [0.0, 0.0]);
+}
+
+fn celestial_bodies(world: &mut World) -> Vec<Entity> {
+	world.query_filtered::<Entity, With<CelestialBody>>().iter(world).collect()
+}
+
+fn debris(world: &mut World) -> Vec<Entity> {
+	world.query_filtered::<Entity, With<Shape>>().iter(world).collect()
+}
+
+#[test]
+fn colliding_planets_merge() {
+	let mut game = Headless::new(scenario_config("colliding_planets_merge", &head_on(100.0)));
+	game.tick_until(60, |world| celestial_bodies(world).len() == 1);
+
+	let merged = celestial_bodies(game.world())[0];
+	let cb = game.get::<CelestialBody>(merged);
+	assert_eq!(cb.mass, 2e6);
+	assert!((cb.radius - CELESTIAL_RADIUS * 2f32.sqrt()).abs() < 1e-3);
+	assert_eq!(game.get::<Orbit>(merged).vel, Vector2::zeros());
+	assert!(debris(game.world()).is_empty());
+}
+
+#[test]
+fn fast_planets_shatter_into_debris() {
+	let config = scenario_config("fast_planets_shatter_into_debris", &head_on(1000.0));
+	let mut game = Headless::new(config);
+	game.tick_until(60, |world| celestial_bodies(world).is_empty());
+	game.tick(1);
+	assert_eq!(debris(game.world()).len(), 2 * SHARDS);
+}
+
+#[test]
+fn planets_chipped_down_to_no_hp_shatter() {
+	let planet = "[[bodies]]\nx = 5000.0\ny = 5000.0\nfixed = true\n";
+	let mut game = Headless::new(scenario_config("planets_chipped_down_to_no_hp_shatter", planet));
+	let planet = celestial_bodies(game.world())[0];
+	let alice = game.spawn_player("alice", 5400.0, 5000.0);
+	game.tick(SETTLE_TICKS);
+
+	// Alice rams the planet, worn down to its last HP.
+	game.world().get_mut::<HP>(planet).unwrap().val = 1;
+	game.launch(alice, 5400.0, 5000.0, Vec2::new(-RAM_SPEED, 0.0));
+	game.tick_until(60, |world| world.get_entity(planet).is_none());
+	game.tick(1);
+	assert_eq!(debris(game.world()).len(), SHARDS);
+}
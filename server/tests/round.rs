@@ -0,0 +1,70 @@
+mod headless;
+
+use bevy::prelude::*;
+
+use game_server::component::{Score, Shape, HP};
+use game_server::config::{Config, WinCondition};
+use game_server::server::GameClock;
+use game_server::GameState;
+
+use headless::{empty_config, Headless};
+
+/// Ticks in a second of the world, rounded up.
+const TICKS_PER_SEC: usize = 63;
+
+fn state(world: &World) -> GameState {
+	world.get_resource::<State<GameState>>().unwrap().current().clone()
+}
+
+fn debris(world: &mut World) -> Vec<Entity> {
+	world.query_filtered::<Entity, With<Shape>>().iter(world).collect()
+}
+
+#[test]
+fn rounds_end_with_a_frozen_world_and_restart() {
+	let config = Config {
+		round_secs: 1,
+		results_secs: 1,
+		debris_count: 3,
+		..empty_config("rounds_end_with_a_frozen_world_and_restart")
+	};
+	let mut game = Headless::new(config);
+	let player = game.spawn_player("alice", 1000.0, 1000.0);
+	game.tick(1);
+	let old_debris = debris(game.world());
+	assert_eq!(old_debris.len(), 3);
+	game.launch(player, 1000.0, 1000.0, Vec2::new(100.0, 0.0));
+	game.world().get_mut::<Score>(player).unwrap().score = 50;
+	game.world().get_mut::<HP>(player).unwrap().val = 60;
+
+	game.tick_until(2 * TICKS_PER_SEC, |world| state(world) == GameState::GameOver);
+	let pos = game.get::<Transform>(player).translation;
+	let time = game.get_resource::<GameClock>().now();
+	game.tick(10);
+	assert_eq!(game.get::<Transform>(player).translation, pos);
+	assert!(game.get_resource::<GameClock>().now() > time);
+
+	// The next round starts in a new world with the scores reset and everyone respawned.
+	game.tick_until(2 * TICKS_PER_SEC, |world| state(world) == GameState::Playing);
+	assert_eq!(game.get::<Score>(player).score, 0);
+	assert_eq!(game.get::<HP>(player).val, 100);
+	let new_debris = debris(game.world());
+	assert_eq!(new_debris.len(), old_debris.len());
+	assert!(new_debris.iter().all(|entity| !old_debris.contains(entity)));
+}
+
+#[test]
+fn rounds_end_once_the_win_condition_is_met() {
+	let config = Config {
+		win_condition: WinCondition::KillLimit(1),
+		..empty_config("rounds_end_once_the_win_condition_is_met")
+	};
+	let mut game = Headless::new(config);
+	let player = game.spawn_player("alice", 1000.0, 1000.0);
+	game.tick(10);
+	assert_eq!(state(game.world()), GameState::Playing);
+
+	game.world().get_mut::<Score>(player).unwrap().kills = 1;
+	game.tick(1);
+	assert_eq!(state(game.world()), GameState::GameOver);
+}
@@ -0,0 +1,35 @@
+use game_server::scenario::Scenario;
+use game_shared::{CelestialForm, CELESTIAL_RADIUS, MAP_HEIGHT, MAP_WIDTH};
+
+#[test]
+fn bundled_scenarios_place_bodies_on_the_map() {
+	for name in Scenario::bundled() {
+		let scenario = Scenario::load(name);
+		assert!(!scenario.bodies.is_empty(), "{} has no bodies", name);
+		for body in scenario.bodies.iter() {
+			assert!(body.x - body.radius > 0.0 && body.x + body.radius < MAP_WIDTH);
+			assert!(body.y - body.radius > 0.0 && body.y + body.radius < MAP_HEIGHT);
+		}
+	}
+}
+
+#[test]
+fn scenario_files_fill_in_defaults() {
+	let path = std::env::temp_dir().join("scenario_files_fill_in_defaults.toml");
+	std::fs::write(&path, "[[bodies]]\nx = 100.0\ny = 200.0\n").unwrap();
+	let scenario = Scenario::load(path.to_str().unwrap());
+	let body = &scenario.bodies[0];
+	assert_eq!(body.form, CelestialForm::Planet);
+	assert_eq!(body.radius, CELESTIAL_RADIUS);
+	assert_eq!(body.velocity, [0.0, 0.0]);
+	assert!(!body.fixed);
+	assert_eq!(body.mass(2.0), 2.0 * body.area());
+}
+
+#[test]
+fn black_holes_are_placed_by_scenarios() {
+	let scenario = Scenario::load("black_hole");
+	let hole = &scenario.bodies[0];
+	assert_eq!(hole.form, CelestialForm::BlackHole);
+	assert!(scenario.bodies[1..].iter().all(|body| body.mass(1.0) < hole.mass(1.0)));
+}
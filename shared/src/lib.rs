@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+use crate::shape::Shape;
+use crate::shield::{BashPhase, ShieldType};
+
+pub mod replay;
+pub mod shape;
+pub mod shield;
+
 pub const MAP_WIDTH: f32 = 10000.0;
 pub const MAP_HEIGHT: f32 = 10000.0;
 pub const VIEW_X: f32 = 2080.0;
@@ -9,10 +16,12 @@ pub const VIEW_Y: f32 = 1170.0;
 pub const INIT_RADIUS: f32 = 20.0;
 pub const SHIELD_RADIUS: f32 = 25.0;
 pub const CELESTIAL_RADIUS: f32 = 100.0;
+/// Energy of players when full, spent on boosts.
+pub const MAX_ENERGY: f32 = 100.0;
 
 #[derive(Serialize, Deserialize)]
 pub enum Operation {
-	Join(String),
+	Join(JoinInfo),
 	Update(PlayerState),
 	Leave,
 }
@@ -27,7 +36,42 @@ impl Operation {
 	}
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize)]
+/// What a player chooses when joining.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct JoinInfo {
+	pub name: String,
+	/// The team to join, or the one with the fewest players if there is none.
+	pub team: Option<u32>,
+	pub shield: ShieldType,
+}
+
+/// Messages sent from the server to clients.
+#[derive(Serialize, Deserialize)]
+pub enum ServerMessage {
+	View(ViewSnapshot),
+	Leaderboard(Vec<ScoreView>),
+	/// The round is over with the final standings.
+	GameOver(Vec<ScoreView>),
+	RoundStart,
+	/// The arena is full and the player waits in the queue at this position, starting from 1.
+	Queued(u32),
+	/// The total scores of the teams, indexed by team.
+	TeamScores(Vec<i32>),
+	/// The geometry of the arena, sent to players when they join.
+	Map(MapView),
+}
+
+impl ServerMessage {
+	pub fn serialize(&self) -> Vec<u8> {
+		bincode::serialize(&self).expect("Cannot serialize ServerMessage.")
+	}
+
+	pub fn deserialize(data: &[u8]) -> Self {
+		bincode::deserialize(data).expect("Cannot deserialize to ServerMessage.")
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Position {
 	pub x: f32,
 	pub y: f32,
@@ -53,6 +97,10 @@ pub struct PlayerState {
 	pub ori: f32,
 	/// If the player pushes shield or not.
 	pub push_shield: bool,
+	/// If the player boosts towards `dir` whenever it has the energy.
+	pub boost: bool,
+	/// If the player bashes with the shield whenever it is ready.
+	pub bash: bool,
 }
 
 impl PlayerState {
@@ -73,6 +121,8 @@ pub struct ViewSnapshot {
 	pub shield_info: Vec<(u64, ShieldView)>,
 	pub static_pos: Vec<(u64, StaticView)>,
 	pub celestial_pos: Vec<(u64, CelestialView)>,
+	/// Energy of the player the view is sent to, if any.
+	pub energy: Option<f32>,
 }
 
 impl ViewSnapshot {
@@ -84,6 +134,7 @@ impl ViewSnapshot {
 			shield_info: Vec::new(),
 			static_pos: Vec::new(),
 			celestial_pos: Vec::new(),
+			energy: None,
 		}
 	}
 
@@ -104,6 +155,8 @@ pub struct PlayerView {
 	pub ori: f32,
 	pub shield_id: u64,
 	pub hp: i32,
+	/// The team of the player, if playing in teams.
+	pub team: Option<u32>,
 }
 
 impl PlayerView {
@@ -120,12 +173,20 @@ impl PlayerView {
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ShieldView {
 	pub pos: Position,
+	/// Rotation of the shield, in whose frame the owner lies along the positive x axis.
+	pub rot: f32,
+	pub shield_type: ShieldType,
 	pub hp: i32,
+	/// Whether the shield ran out of HP and doesn't block anything until it regenerates.
+	pub broken: bool,
+	pub bash: BashPhase,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct StaticView {
 	pub pos: Position,
+	pub rot: f32,
+	pub shape: Shape,
 	pub hp: i32,
 }
 
@@ -139,9 +200,22 @@ impl StaticView {
 	}
 }
 
+/// The kinds of celestial bodies, which are drawn differently.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CelestialForm {
+	#[default]
+	Planet,
+	Sun,
+	/// Swallows whatever crosses the event horizon at its radius.
+	BlackHole,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CelestialView {
 	pub pos: Position,
+	pub radius: f32,
+	pub form: CelestialForm,
 	pub hp: i32,
 }
 
@@ -155,6 +229,34 @@ impl CelestialView {
 	}
 }
 
+/// The walls and static obstacles of the arena, in absolute positions.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MapView {
+	pub width: f32,
+	pub height: f32,
+	/// Polylines of walls, each point joined to the next.
+	pub boundaries: Vec<Vec<Position>>,
+	pub obstacles: Vec<ObstacleView>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObstacleView {
+	pub pos: Position,
+	pub rot: f32,
+	pub shape: Shape,
+}
+
+/// An entry of the leaderboard.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoreView {
+	pub name: String,
+	pub team: Option<u32>,
+	pub kills: u32,
+	pub assists: u32,
+	pub deaths: u32,
+	pub score: i32,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub enum EffectType {
 	BodyDamage(f32, f32),
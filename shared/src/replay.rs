@@ -0,0 +1,72 @@
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{JoinInfo, MapView, PlayerState, ViewSnapshot};
+
+/// Information about a recorded session, written at the beginning of a replay file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayHeader {
+	/// Seed of the world's RNG if it is seeded.
+	pub seed: Option<u64>,
+	/// The server config in TOML.
+	pub config: String,
+	pub tick_time: Duration,
+}
+
+/// An inbound event recorded with the entity of the player (as bits) it concerns.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RecordedEvent {
+	/// The player joined with all of its choices, so that its team and shield can be reproduced.
+	Join(u64, JoinInfo),
+	Update(u64, PlayerState),
+	Leave(u64),
+}
+
+/// Records following the header, each tagged with the tick it happened at.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ReplayRecord {
+	/// Inbound events of a tick. Ticks without events are not recorded.
+	Events(u64, Vec<RecordedEvent>),
+	/// The state of the whole world.
+	World(u64, ViewSnapshot),
+	/// The geometry of the arena, recorded once at the start.
+	Map(u64, MapView),
+}
+
+impl ReplayHeader {
+	pub fn write_to<W: Write>(&self, writer: W) {
+		bincode::serialize_into(writer, &self).expect("Cannot serialize ReplayHeader.")
+	}
+}
+
+impl ReplayRecord {
+	pub fn write_to<W: Write>(&self, writer: W) {
+		bincode::serialize_into(writer, &self).expect("Cannot serialize ReplayRecord.")
+	}
+}
+
+/// A replay file loaded into memory.
+pub struct Replay {
+	pub header: ReplayHeader,
+	pub records: Vec<ReplayRecord>,
+}
+
+impl Replay {
+	/// Read a replay from `reader`.
+	/// A truncated record at the end, e.g. from a server that got killed, is dropped.
+	pub fn read_from<R: Read>(mut reader: R) -> Self {
+		let header =
+			bincode::deserialize_from(&mut reader).expect("Cannot deserialize to ReplayHeader.");
+		let mut records = Vec::new();
+		while let Ok(record) = bincode::deserialize_from(&mut reader) {
+			records.push(record);
+		}
+		Replay { header, records }
+	}
+
+	pub fn deserialize(data: &[u8]) -> Self {
+		Replay::read_from(data)
+	}
+}
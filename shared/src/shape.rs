@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Position;
+
+/// The geometry of debris and obstacles, centered at their position.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shape {
+	Circle { radius: f32 },
+	Rect { half_width: f32, half_height: f32 },
+	/// A convex polygon with `points` in counterclockwise order.
+	Polygon { points: Vec<Position> },
+	/// A segment of `half_length` along the x axis with rounded ends of `radius`.
+	Capsule { half_length: f32, radius: f32 },
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Shield archetypes players choose from when joining.
+///
+/// The geometry is given in the frame of the shield, where the player's body lies
+/// along the positive x axis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShieldType {
+	/// A ball of `SHIELD_RADIUS`.
+	#[default]
+	Circle,
+	/// An arc of `ARC_RADIUS` curving around the body.
+	WideArc,
+	/// A triangle pointing away from the body, dealing more damage.
+	Spike,
+	/// A heavy slab moving slowly.
+	Bulwark,
+	/// A small ball of `LIGHT_RADIUS` moving fast.
+	Light,
+}
+
+impl ShieldType {
+	/// Every archetype, in the order they are offered to players.
+	pub const ALL: [ShieldType; 5] = [
+		ShieldType::Circle,
+		ShieldType::WideArc,
+		ShieldType::Spike,
+		ShieldType::Bulwark,
+		ShieldType::Light,
+	];
+}
+
+/// Where a shield is in its bash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BashPhase {
+	Ready,
+	/// Driven out and hitting harder.
+	Active,
+	/// Waiting to bash again.
+	Cooldown,
+}
+
+pub const ARC_RADIUS: f32 = 50.0;
+pub const ARC_THICKNESS: f32 = 12.0;
+/// The angle the arc spans.
+pub const ARC_SPAN: f32 = 1.6;
+pub const SPIKE_LENGTH: f32 = 45.0;
+pub const SPIKE_WIDTH: f32 = 30.0;
+/// Half of the extents of the slab.
+pub const BULWARK_HALF_EXTENTS: (f32, f32) = (12.0, 40.0);
+pub const LIGHT_RADIUS: f32 = 15.0;